@@ -0,0 +1,221 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::{log, prelude::*};
+use serde::Deserialize;
+
+use crate::{
+    common::{Direction, load_ron_or},
+    components::{Ghost, Movable},
+    events::AnimationFinishedEvent,
+    ghosts::ghost_mode::{FrightenedRes, GhostMode},
+};
+
+/// Used when the animations of the theme can't be loaded.
+const DEFAULT_ANIMATIONS: &str = include_str!("../assets/config/animations.ron");
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub enum LoopMode {
+    Loop,
+    /// Plays once and holds the last frame, sending an [AnimationFinishedEvent].
+    Once,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnimationClip {
+    /// Atlas indices, counted row by row.
+    pub frames: Vec<usize>,
+    pub frame_seconds: f32,
+    pub mode: LoopMode,
+}
+
+/// A grid of square sprites in a spritesheet.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AtlasGrid {
+    pub sprite_size: u32,
+    pub columns: u32,
+    pub rows: u32,
+    pub offset: (u32, u32),
+}
+
+impl AtlasGrid {
+    pub fn layout(&self) -> TextureAtlasLayout {
+        TextureAtlasLayout::from_grid(
+            UVec2::splat(self.sprite_size),
+            self.columns,
+            self.rows,
+            None,
+            Some(UVec2::new(self.offset.0, self.offset.1)),
+        )
+    }
+}
+
+/// The animation clips of every character by name and state, see `assets/config/animations.ron`.
+#[derive(Resource, Deserialize)]
+pub struct AnimationLibrary {
+    pub grid: AtlasGrid,
+    characters: HashMap<String, HashMap<String, AnimationClip>>,
+}
+
+impl AnimationLibrary {
+    pub fn load_or_default(path: &str) -> Self {
+        load_ron_or(path, "animations", Self::default)
+    }
+
+    /// An [Animation] of [character], starting with the [initial] clip.
+    pub fn animation(&self, character: &str, initial: &str) -> Animation {
+        let clips = self.characters.get(character).cloned().unwrap_or_else(|| {
+            log::error!("No animations for {character}");
+            HashMap::new()
+        });
+
+        Animation::new(clips, initial)
+    }
+}
+
+impl Default for AnimationLibrary {
+    fn default() -> Self {
+        ron::from_str(DEFAULT_ANIMATIONS).expect("The default animations should be valid")
+    }
+}
+
+/// The clip for moving in [dir].
+pub fn direction_clip(dir: &Direction) -> &'static str {
+    match dir {
+        Direction::Up => "up",
+        Direction::Right => "right",
+        Direction::Down => "down",
+        Direction::Left => "left",
+    }
+}
+
+fn eyes_clip(dir: &Direction) -> &'static str {
+    match dir {
+        Direction::Up => "eyes_up",
+        Direction::Right => "eyes_right",
+        Direction::Down => "eyes_down",
+        Direction::Left => "eyes_left",
+    }
+}
+
+/// Plays the animation clips of a character.
+#[derive(Component)]
+pub struct Animation {
+    clips: HashMap<String, AnimationClip>,
+    current: String,
+    frame: usize,
+    timer: Timer,
+    /// Set while a clip started with [Animation::play] runs, it's kept until stopped.
+    locked: bool,
+    finished: bool,
+}
+
+impl Animation {
+    fn new(clips: HashMap<String, AnimationClip>, initial: &str) -> Self {
+        let mut animation = Self {
+            clips,
+            current: String::new(),
+            frame: 0,
+            timer: Timer::default(),
+            locked: false,
+            finished: false,
+        };
+        animation.start(initial);
+        animation
+    }
+
+    fn start(&mut self, name: &str) {
+        let Some(clip) = self.clips.get(name) else {
+            log::warn!("No animation clip named {name}");
+            return;
+        };
+
+        self.timer = Timer::from_seconds(clip.frame_seconds, TimerMode::Repeating);
+        self.current = name.to_string();
+        self.frame = 0;
+        self.finished = false;
+    }
+
+    /// The atlas index of the current frame.
+    pub fn index(&self) -> usize {
+        self.clips
+            .get(&self.current)
+            .and_then(|clip| clip.frames.get(self.frame))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Switches to the clip [name], unless it's already playing or a clip started with
+    /// [Animation::play] hasn't been stopped yet.
+    pub fn switch(&mut self, name: &str) {
+        if self.locked || self.current == name {
+            return;
+        }
+
+        self.start(name);
+    }
+
+    /// Plays the clip [name] from its first frame, keeping it until [Animation::stop].
+    pub fn play(&mut self, name: &str) {
+        self.start(name);
+        self.locked = true;
+    }
+
+    pub fn stop(&mut self) {
+        self.locked = false;
+    }
+
+    /// Moves the current clip along, returning its name if it's a one-shot clip that just
+    /// played its last frame.
+    pub fn tick(&mut self, delta: Duration) -> Option<String> {
+        let clip = self.clips.get(&self.current)?;
+        if !self.timer.tick(delta).just_finished() || self.finished {
+            return None;
+        }
+
+        if self.frame + 1 < clip.frames.len() {
+            self.frame += 1;
+            None
+        } else if clip.mode == LoopMode::Loop {
+            self.frame = 0;
+            None
+        } else {
+            // Hold the last frame.
+            self.finished = true;
+            Some(self.current.clone())
+        }
+    }
+}
+
+/// Picks the clip for the state characters are in, like the direction they're moving in.
+pub fn select_animation_clips(
+    frightened: Res<FrightenedRes>,
+    mut query: Query<(&mut Animation, &Movable, Option<&Ghost>)>,
+) {
+    for (mut animation, movable, ghost) in &mut query {
+        let mode = ghost.map(|g| &g.current_mode);
+        let clip = match mode {
+            Some(GhostMode::Frightened) if frightened.is_flashing() => "flashing",
+            Some(GhostMode::Frightened) => "frightened",
+            Some(GhostMode::Eaten) => eyes_clip(&movable.direction),
+            _ => direction_clip(&movable.direction),
+        };
+
+        animation.switch(clip);
+    }
+}
+
+pub fn animate_sprite(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Animation, &mut Sprite)>,
+    mut animation_events: EventWriter<AnimationFinishedEvent>,
+) {
+    for (entity, mut animation, mut sprite) in &mut query {
+        if let Some(clip) = animation.tick(time.delta()) {
+            animation_events.write(AnimationFinishedEvent { entity, clip });
+        }
+
+        if let Some(atlas) = &mut sprite.texture_atlas {
+            atlas.index = animation.index();
+        }
+    }
+}
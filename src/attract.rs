@@ -0,0 +1,392 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    animation::{Animation, AnimationLibrary},
+    common::TilePos,
+    components::{Ghost, Movable, Player, Position, QueableDirection},
+    fruit::DotsEaten,
+    game_state::GameState,
+    ghosts::{
+        GhostName,
+        ghost_mode::{FrightenedRes, GhostMode, GhostModeRes},
+    },
+    input::{Action, ActionState},
+    level::Level,
+    lives::Lives,
+    map::{MAP, MAP_HEIGHT, MAP_WIDTH},
+    players::Players,
+    popup::ScorePopup,
+    rewind::RewindBuffer,
+    rng::GameRng,
+    rules::GameRules,
+    score::{Scorable, Score},
+    theme::Theme,
+};
+
+/// How long the title screen is shown before the demo starts.
+const TITLE_SECONDS: f32 = 8.;
+/// The longest the demo plays before going back to the title screen.
+const DEMO_SECONDS: f32 = 20.;
+/// How long the game over screen is shown before going back to the title screen.
+const GAME_OVER_SECONDS: f32 = 8.;
+/// The demo Pac-Man keeps out of tiles this close to a ghost that can catch him.
+const DEMO_DANGER_DISTANCE: f32 = 3.;
+
+const ROSTER: [(GhostName, &str); 4] = [
+    (GhostName::Blinky, "SHADOW"),
+    (GhostName::Pinky, "SPEEDY"),
+    (GhostName::Inky, "BASHFUL"),
+    (GhostName::Clyde, "POKEY"),
+];
+
+/// Exists while the demo game is playing, Pac-Man is steered by [steer_demo_pacman].
+#[derive(Resource)]
+pub struct Demo {
+    timer: Timer,
+}
+
+/// Counts down the title and game over screens.
+#[derive(Resource)]
+pub struct AttractTimer(Timer);
+
+pub fn run_if_demo(demo: Option<Res<Demo>>) -> bool {
+    demo.is_some()
+}
+
+/// Whether any key or bound button was just pressed.
+fn any_input(keyboard_input: &ButtonInput<KeyCode>, actions: &ActionState) -> bool {
+    keyboard_input.get_just_pressed().next().is_some() || actions.any_just_pressed()
+}
+
+pub fn setup_title_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    animations: Res<AnimationLibrary>,
+    theme: Res<Theme>,
+) {
+    commands.remove_resource::<Demo>();
+    commands.insert_resource(AttractTimer(Timer::from_seconds(
+        TITLE_SECONDS,
+        TimerMode::Once,
+    )));
+
+    // The title screen is shown before the world is set up, so it has its own atlas layouts.
+    let texture: Handle<Image> = asset_server.load(&theme.spritesheet);
+    let character_layout = texture_atlas_layouts.add(animations.grid.layout());
+    let maze_layout = texture_atlas_layouts.add(theme.maze_grid.layout());
+
+    let text_font = TextFont {
+        font: asset_server.load(&theme.font),
+        font_size: 20.0,
+        ..default()
+    };
+
+    let icon = |layout: &Handle<TextureAtlasLayout>, index: usize, size: f32| {
+        (
+            ImageNode::from_atlas_image(
+                texture.clone(),
+                TextureAtlas {
+                    layout: layout.clone(),
+                    index,
+                },
+            ),
+            Node {
+                width: Val::Px(size),
+                height: Val::Px(size),
+                ..default()
+            },
+        )
+    };
+
+    let row = Node {
+        width: Val::Px(480.),
+        align_items: AlignItems::Center,
+        column_gap: Val::Px(24.),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            StateScoped(GameState::Title),
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(24.),
+                ..default()
+            },
+            BackgroundColor(theme.colours.background()),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("CHARACTER / NICKNAME"),
+                text_font.clone(),
+                TextColor(theme.colours.text()),
+            ));
+
+            for (ghost, nickname) in ROSTER {
+                let index = animations.animation(ghost.animation_name(), "right").index();
+                parent.spawn(row.clone()).with_children(|parent| {
+                    parent.spawn(icon(&character_layout, index, 32.));
+                    parent.spawn((
+                        Text::new(format!(
+                            "{nickname} - \"{}\"",
+                            ghost.to_string().to_uppercase()
+                        )),
+                        text_font.clone(),
+                        TextColor(ghost.get_color()),
+                    ));
+                });
+            }
+
+            for (index, points) in [(theme.dot, 10), (theme.energizer, 50)] {
+                parent
+                    .spawn(Node {
+                        width: Val::Px(160.),
+                        ..row.clone()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(icon(&maze_layout, index, 16.));
+                        parent.spawn((
+                            Text::new(format!("{points} PTS")),
+                            text_font.clone().with_font_size(16.),
+                            TextColor(theme.colours.text()),
+                        ));
+                    });
+            }
+
+            parent.spawn((
+                Text::new("PRESS ANY KEY TO START\nPRESS 2 FOR TWO PLAYERS\nPRESS 3 FOR CO-OP\nPRESS 4 FOR VERSUS\nPRESS F9 TO LOAD A SAVED GAME"),
+                text_font.clone().with_font_size(16.),
+                TextColor(theme.colours.highlight()),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+        });
+}
+
+/// Starts a game when a key is pressed (a two player game for [Action::StartTwoPlayers], co-op
+/// for [Action::StartCoop], versus for [Action::StartVersus]), or the demo once the title has been shown for a while.
+pub fn update_title_screen(
+    mut commands: Commands,
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
+    mut timer: ResMut<AttractTimer>,
+    mut players: ResMut<Players>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if any_input(&keyboard_input, &actions) {
+        players.count = if actions.just_pressed(Action::StartTwoPlayers) {
+            2
+        } else {
+            1
+        };
+        players.coop = actions.just_pressed(Action::StartCoop);
+        players.versus = actions.just_pressed(Action::StartVersus);
+        next_state.set(GameState::Playing);
+        return;
+    }
+
+    if timer.0.tick(time.delta()).finished() {
+        players.count = 1;
+        players.coop = false;
+        players.versus = false;
+        commands.insert_resource(Demo {
+            timer: Timer::from_seconds(DEMO_SECONDS, TimerMode::Once),
+        });
+        next_state.set(GameState::Playing);
+    }
+}
+
+/// Goes back to the title screen when a key is pressed or the demo has played long enough.
+pub fn update_demo(
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
+    mut demo: ResMut<Demo>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if demo.timer.tick(time.delta()).finished() || any_input(&keyboard_input, &actions) {
+        next_state.set(GameState::Title);
+    }
+}
+
+pub fn start_game_over_timer(mut commands: Commands) {
+    commands.insert_resource(AttractTimer(Timer::from_seconds(
+        GAME_OVER_SECONDS,
+        TimerMode::Once,
+    )));
+}
+
+pub fn leave_game_over(
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
+    mut timer: ResMut<AttractTimer>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if timer.0.tick(time.delta()).finished() || any_input(&keyboard_input, &actions) {
+        next_state.set(GameState::Title);
+    }
+}
+
+/// Starts over from the first level with a full maze, for a real game and the demo alike.
+pub fn start_new_game(
+    mut score: ResMut<Score>,
+    mut lives: ResMut<Lives>,
+    mut level: ResMut<Level>,
+    mut dots_eaten: ResMut<DotsEaten>,
+    mut mode_res: ResMut<GhostModeRes>,
+    mut frightened: ResMut<FrightenedRes>,
+    mut players: ResMut<Players>,
+) {
+    *score = Score::new();
+    *lives = Lives::default();
+    *level = Level::default();
+    *dots_eaten = DotsEaten::default();
+    *mode_res = GhostModeRes::default();
+    *frightened = FrightenedRes::default();
+    players.reset();
+}
+
+/// Clears what the previous game (or demo) might have left halfway.
+pub fn clear_previous_game(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    mut rewind: ResMut<RewindBuffer>,
+    popups: Query<Entity, With<ScorePopup>>,
+    animations: Query<&mut Animation>,
+) {
+    // Every game starts from the seed, so it can be played out again.
+    rng.rewind();
+    rewind.clear();
+
+    for popup in &popups {
+        commands.entity(popup).despawn();
+    }
+
+    // Like the demo being left during the death clip.
+    for mut animation in animations {
+        animation.stop();
+    }
+}
+
+/// A grid over the maze tiles, anything outside of it (like the tunnel ends) reads as unset.
+struct TileGrid([[bool; MAP_WIDTH]; MAP_HEIGHT]);
+
+impl TileGrid {
+    fn new() -> Self {
+        Self([[false; MAP_WIDTH]; MAP_HEIGHT])
+    }
+
+    fn index(tile: &TilePos) -> Option<(usize, usize)> {
+        let in_maze =
+            (0..MAP_WIDTH as i32).contains(&tile.x) && (0..MAP_HEIGHT as i32).contains(&tile.y);
+        in_maze.then_some((tile.y as usize, tile.x as usize))
+    }
+
+    fn get(&self, tile: &TilePos) -> bool {
+        Self::index(tile).is_some_and(|(y, x)| self.0[y][x])
+    }
+
+    fn set(&mut self, tile: &TilePos) {
+        if let Some((y, x)) = Self::index(tile) {
+            self.0[y][x] = true;
+        }
+    }
+}
+
+/// The number of steps from [start] to the closest tile in [dots], not passing through
+/// [danger].
+fn distance_to_closest_dot(start: &TilePos, dots: &TileGrid, danger: &TileGrid) -> Option<u32> {
+    let mut visited = TileGrid::new();
+    let mut queue = VecDeque::from([(start.clone(), 0)]);
+    visited.set(start);
+
+    while let Some((tile, distance)) = queue.pop_front() {
+        if dots.get(&tile) {
+            return Some(distance);
+        }
+
+        for (neighbour, _) in MAP.get_empty_neighbours(&tile) {
+            if TileGrid::index(&neighbour).is_none()
+                || visited.get(&neighbour)
+                || danger.get(&neighbour)
+            {
+                continue;
+            }
+
+            visited.set(&neighbour);
+            queue.push_back((neighbour, distance + 1));
+        }
+    }
+
+    None
+}
+
+/// The demo "player": on entering a tile, queues the way out of it that leads to the closest
+/// dot while keeping clear of the ghosts. Moving and eating is left to the regular systems.
+pub fn steer_demo_pacman(
+    rules: Res<GameRules>,
+    pacman: Single<(&Position, &Movable, &mut QueableDirection), With<Player>>,
+    ghosts: Query<(&Position, &Ghost)>,
+    scorables: Query<(&Position, &Scorable)>,
+    mut last_tile: Local<Option<TilePos>>,
+) {
+    let (position, movable, mut queued_dir) = pacman.into_inner();
+    let tile: TilePos = (&position.0).into();
+    if last_tile.as_ref() == Some(&tile) {
+        return;
+    }
+    *last_tile = Some(tile.clone());
+
+    let mut dots = TileGrid::new();
+    for (position, scorable) in &scorables {
+        if matches!(scorable, Scorable::Dot | Scorable::Energizer) {
+            dots.set(&(&position.0).into());
+        }
+    }
+
+    let threats = ghosts
+        .iter()
+        .filter(|(_, ghost)| matches!(ghost.current_mode, GhostMode::Chase | GhostMode::Scatter))
+        .map(|(position, _)| TilePos::from(&position.0))
+        .collect::<Vec<_>>();
+
+    let mut danger = TileGrid::new();
+    for y in 0..MAP_HEIGHT as i32 {
+        for x in 0..MAP_WIDTH as i32 {
+            let candidate = TilePos { x, y };
+            if threats
+                .iter()
+                .any(|t| t.dist_to(&candidate) < DEMO_DANGER_DISTANCE)
+            {
+                danger.set(&candidate);
+            }
+        }
+    }
+
+    let best = MAP
+        .get_empty_neighbours(&tile)
+        .into_iter()
+        .min_by_key(|(neighbour, dir)| {
+            let mut cost = if danger.get(neighbour) { 1_000 } else { 0 };
+            cost += distance_to_closest_dot(neighbour, &dots, &danger).unwrap_or(100);
+            if dir == &movable.direction.opposite() {
+                // Only turn around when it's clearly better.
+                cost += 4;
+            }
+            cost
+        })
+        .map(|(_, dir)| dir);
+
+    match best {
+        Some(dir) if dir != movable.direction => queued_dir.queue(dir, &rules.input_buffer),
+        _ => queued_dir.clear(),
+    }
+}
@@ -0,0 +1,257 @@
+use std::fs;
+
+use bevy::{
+    audio::{AudioSinkPlayback, Volume},
+    log,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::load_ron_or,
+    components::Ghost,
+    events::{PacmanDeathEvent, ScorableEatenEvent, ScoreMilestoneEvent},
+    ghosts::ghost_mode::{FrightenedRes, GhostMode},
+    input::{Action, ActionState},
+    pause::PauseRes,
+    score::{Milestone, Scorable},
+};
+
+pub const AUDIO_SETTINGS_PATH: &str = "audio_settings.ron";
+const VOLUME_STEP: f32 = 0.1;
+/// The background siren speeds up when the remaining dots drop below each of these.
+const SIREN_DOT_THRESHOLDS: [usize; 4] = [160, 100, 60, 30];
+
+#[derive(Resource, Serialize, Deserialize)]
+pub struct AudioSettings {
+    /// 0.0 - 1.0
+    pub volume: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            volume: 0.5,
+            muted: false,
+        }
+    }
+}
+
+impl AudioSettings {
+    pub fn load_or_default(path: &str) -> Self {
+        load_ron_or(path, "audio settings", Self::default)
+    }
+
+    pub fn save(&self, path: &str) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(err) => {
+                log::error!("Failed to serialize audio settings ({err})");
+                return;
+            }
+        };
+
+        if let Err(err) = fs::write(path, contents) {
+            log::error!("Failed to save audio settings to {path} ({err})");
+        }
+    }
+
+    fn effective_volume(&self) -> Volume {
+        Volume::Linear(if self.muted { 0. } else { self.volume })
+    }
+}
+
+/// The sound effects, loaded up front so they play without delay.
+#[derive(Resource)]
+pub struct Sounds {
+    waka: [Handle<AudioSource>; 2],
+    sirens: [Handle<AudioSource>; 5],
+    energizer: Handle<AudioSource>,
+    eyes: Handle<AudioSource>,
+    ghost_eaten: Handle<AudioSource>,
+    fruit: Handle<AudioSource>,
+    extra_life: Handle<AudioSource>,
+    death: Handle<AudioSource>,
+    intro: Handle<AudioSource>,
+}
+
+impl FromWorld for Sounds {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            waka: [
+                asset_server.load("sounds/waka_1.wav"),
+                asset_server.load("sounds/waka_2.wav"),
+            ],
+            sirens: [
+                asset_server.load("sounds/siren_1.wav"),
+                asset_server.load("sounds/siren_2.wav"),
+                asset_server.load("sounds/siren_3.wav"),
+                asset_server.load("sounds/siren_4.wav"),
+                asset_server.load("sounds/siren_5.wav"),
+            ],
+            energizer: asset_server.load("sounds/energizer.wav"),
+            eyes: asset_server.load("sounds/eyes.wav"),
+            ghost_eaten: asset_server.load("sounds/ghost_eaten.wav"),
+            fruit: asset_server.load("sounds/fruit.wav"),
+            extra_life: asset_server.load("sounds/extra_life.wav"),
+            death: asset_server.load("sounds/death.wav"),
+            intro: asset_server.load("sounds/intro.wav"),
+        }
+    }
+}
+
+/// The looping sound in the background, only one plays at a time.
+#[derive(Component)]
+pub struct BackgroundLoop(Handle<AudioSource>);
+
+#[derive(Component)]
+pub struct IntroJingle;
+
+fn play(commands: &mut Commands, sound: &Handle<AudioSource>) {
+    commands.spawn((AudioPlayer::new(sound.clone()), PlaybackSettings::DESPAWN));
+}
+
+pub fn play_intro_jingle(mut commands: Commands, sounds: Res<Sounds>) {
+    commands.spawn((
+        AudioPlayer::new(sounds.intro.clone()),
+        PlaybackSettings::DESPAWN,
+        IntroJingle,
+    ));
+}
+
+/// Waka-waka while eating dots, and the jingles for eating ghosts and fruit.
+pub fn play_eat_sounds(
+    mut commands: Commands,
+    sounds: Res<Sounds>,
+    mut eaten_events: EventReader<ScorableEatenEvent>,
+    mut next_waka: Local<usize>,
+) {
+    for event in eaten_events.read() {
+        match event.scorable {
+            Scorable::Dot | Scorable::Energizer => {
+                play(&mut commands, &sounds.waka[*next_waka]);
+                *next_waka = (*next_waka + 1) % sounds.waka.len();
+            }
+            Scorable::Ghost => play(&mut commands, &sounds.ghost_eaten),
+            Scorable::Fruit(_) => play(&mut commands, &sounds.fruit),
+        }
+    }
+}
+
+pub fn play_extra_life_sound(
+    mut commands: Commands,
+    sounds: Res<Sounds>,
+    mut milestone_events: EventReader<ScoreMilestoneEvent>,
+) {
+    for event in milestone_events.read() {
+        if event.milestone == Milestone::ExtraLife {
+            play(&mut commands, &sounds.extra_life);
+        }
+    }
+}
+
+pub fn play_death_sound(
+    mut commands: Commands,
+    sounds: Res<Sounds>,
+    mut death_events: EventReader<PacmanDeathEvent>,
+) {
+    if death_events.read().count() > 0 {
+        play(&mut commands, &sounds.death);
+    }
+}
+
+/// Picks the background loop: eyes returning home over the energizer siren over the regular
+/// siren, which escalates as fewer dots remain.
+pub fn update_background_loop(
+    mut commands: Commands,
+    sounds: Res<Sounds>,
+    frightened: Res<FrightenedRes>,
+    ghosts: Query<&Ghost>,
+    dots: Query<&Scorable>,
+    intro: Query<(), With<IntroJingle>>,
+    current: Query<(Entity, &BackgroundLoop)>,
+) {
+    let wanted = if !intro.is_empty() {
+        None
+    } else if ghosts.iter().any(|g| g.current_mode == GhostMode::Eaten) {
+        Some(&sounds.eyes)
+    } else if frightened.timer.is_some() {
+        Some(&sounds.energizer)
+    } else {
+        let remaining = dots
+            .iter()
+            .filter(|s| matches!(s, Scorable::Dot | Scorable::Energizer))
+            .count();
+        let stage = SIREN_DOT_THRESHOLDS
+            .iter()
+            .filter(|&&threshold| remaining < threshold)
+            .count();
+        Some(&sounds.sirens[stage])
+    };
+
+    let mut already_playing = false;
+    for (entity, background_loop) in &current {
+        if Some(&background_loop.0) == wanted {
+            already_playing = true;
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    if let (Some(sound), false) = (wanted, already_playing) {
+        commands.spawn((
+            AudioPlayer::new(sound.clone()),
+            PlaybackSettings::LOOP,
+            BackgroundLoop(sound.clone()),
+        ));
+    }
+}
+
+/// Silences the background loop when the action stops, like on death or game over.
+pub fn stop_background_loop(mut commands: Commands, current: Query<Entity, With<BackgroundLoop>>) {
+    for entity in &current {
+        commands.entity(entity).despawn();
+    }
+}
+
+pub fn pause_background_loop(pause: Res<PauseRes>, sinks: Query<&AudioSink, With<BackgroundLoop>>) {
+    for sink in &sinks {
+        if pause.paused {
+            sink.pause();
+        } else {
+            sink.play();
+        }
+    }
+}
+
+pub fn change_volume(
+    actions: Res<ActionState>,
+    mut settings: ResMut<AudioSettings>,
+    mut global_volume: ResMut<GlobalVolume>,
+    mut sinks: Query<&mut AudioSink>,
+) {
+    if actions.just_pressed(Action::VolumeUp) {
+        settings.volume = (settings.volume + VOLUME_STEP).min(1.);
+        settings.muted = false;
+    } else if actions.just_pressed(Action::VolumeDown) {
+        settings.volume = (settings.volume - VOLUME_STEP).max(0.);
+    } else if actions.just_pressed(Action::ToggleMute) {
+        settings.muted = !settings.muted;
+    } else {
+        return;
+    }
+
+    settings.save(AUDIO_SETTINGS_PATH);
+
+    // The global volume is only applied to sounds when they start, so update the playing ones too.
+    global_volume.volume = settings.effective_volume();
+    for mut sink in &mut sinks {
+        sink.set_volume(settings.effective_volume());
+    }
+}
+
+pub fn apply_audio_settings(settings: Res<AudioSettings>, mut global_volume: ResMut<GlobalVolume>) {
+    global_volume.volume = settings.effective_volume();
+}
@@ -0,0 +1,84 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    hud::{HUD_ROWS_ABOVE, HUD_ROWS_BELOW},
+    input::{Action, ActionState},
+    map::{MAP_HEIGHT, MAP_WIDTH, TILE_SIZE},
+};
+
+/// The size in pixels of everything that should always be visible, the maze plus the HUD.
+pub const PLAYFIELD_WIDTH: f32 = (MAP_WIDTH as i32 * TILE_SIZE) as f32;
+pub const PLAYFIELD_HEIGHT: f32 =
+    ((MAP_HEIGHT as i32 + HUD_ROWS_ABOVE + HUD_ROWS_BELOW) * TILE_SIZE) as f32;
+
+/// The initial window size, the playfield at 3x zoom.
+pub const DEFAULT_WINDOW_SIZE: Vec2 = Vec2::new(PLAYFIELD_WIDTH * 3., PLAYFIELD_HEIGHT * 3.);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZoomMode {
+    /// Zooms by the largest whole number that fits in the window, keeping every pixel the same size.
+    PixelPerfect,
+    /// Zooms as far as possible to fill the window.
+    Fill,
+}
+
+#[derive(Resource)]
+pub struct CameraSettings {
+    pub zoom_mode: ZoomMode,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            zoom_mode: ZoomMode::PixelPerfect,
+        }
+    }
+}
+
+/// The centre of the playfield in world coordinates.
+fn playfield_center() -> Vec2 {
+    let top = (HUD_ROWS_ABOVE * TILE_SIZE) as f32;
+    Vec2::new(PLAYFIELD_WIDTH / 2., top - PLAYFIELD_HEIGHT / 2.)
+}
+
+pub fn setup_camera(mut commands: Commands) {
+    commands.spawn((
+        Camera2d,
+        Projection::Orthographic(OrthographicProjection::default_2d()),
+        Transform::from_translation(playfield_center().extend(0.)),
+    ));
+}
+
+pub fn toggle_zoom_mode(actions: Res<ActionState>, mut settings: ResMut<CameraSettings>) {
+    if actions.just_pressed(Action::ToggleZoomMode) {
+        settings.zoom_mode = match settings.zoom_mode {
+            ZoomMode::PixelPerfect => ZoomMode::Fill,
+            ZoomMode::Fill => ZoomMode::PixelPerfect,
+        };
+    }
+}
+
+/// Zooms the camera so the playfield fits the window, anything left over is letterboxed.
+pub fn fit_camera_to_window(
+    window: Single<&Window, With<PrimaryWindow>>,
+    settings: Res<CameraSettings>,
+    camera: Single<(&mut Projection, &mut Transform), With<Camera2d>>,
+) {
+    let (mut projection, mut transform) = camera.into_inner();
+    let Projection::Orthographic(projection) = projection.as_mut() else {
+        return;
+    };
+
+    let physical_size = window.physical_size().as_vec2();
+    let fit = (physical_size.x / PLAYFIELD_WIDTH).min(physical_size.y / PLAYFIELD_HEIGHT);
+
+    // The amount of physical pixels per playfield pixel.
+    let zoom = match settings.zoom_mode {
+        ZoomMode::PixelPerfect => fit.floor().max(1.),
+        ZoomMode::Fill => fit,
+    };
+
+    projection.scale = window.scale_factor() / zoom;
+
+    transform.translation = playfield_center().extend(transform.translation.z);
+}
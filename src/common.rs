@@ -1,6 +1,7 @@
 use std::{
     cmp::Ordering,
     fmt::Display,
+    fs, io,
     ops::{Add, Sub},
 };
 
@@ -8,9 +9,10 @@ use crate::{
     ghosts::GhostName,
     map::{TILE_CENTER_PIXEL_OFFSET_X, TILE_CENTER_PIXEL_OFFSET_Y, TILE_SIZE},
 };
-use bevy::prelude::*;
+use bevy::{log, prelude::*};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
-#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Serialize, Deserialize)]
 pub enum Direction {
     Up,
     Right,
@@ -50,7 +52,7 @@ impl Ord for Direction {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct TilePos {
     pub x: i32,
     pub y: i32,
@@ -95,7 +97,7 @@ impl TilePos {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PixelPos {
     pub x: i32,
     pub y: i32,
@@ -150,6 +152,36 @@ impl PixelPos {
         self.x.abs() % TILE_SIZE == TILE_CENTER_PIXEL_OFFSET_X
             && self.y.abs() % TILE_SIZE == TILE_CENTER_PIXEL_OFFSET_Y
     }
+
+    /// Returns the (x, y) offset in pixels from the centre of the tile this position is in.
+    pub fn offset_from_tile_center(&self) -> (i32, i32) {
+        (
+            self.x.rem_euclid(TILE_SIZE) - TILE_CENTER_PIXEL_OFFSET_X,
+            self.y.rem_euclid(TILE_SIZE) - TILE_CENTER_PIXEL_OFFSET_Y,
+        )
+    }
+
+    /// Returns the distance in pixels from the centre of the tile along the axis of [dir],
+    /// negative before reaching the centre and positive after having passed it.
+    pub fn offset_along(&self, dir: &Direction) -> i32 {
+        let (offset_x, offset_y) = self.offset_from_tile_center();
+        match dir {
+            Direction::Up => -offset_y,
+            Direction::Right => offset_x,
+            Direction::Down => offset_y,
+            Direction::Left => -offset_x,
+        }
+    }
+
+    /// Moves one pixel towards the centre line of the lane when travelling in [dir].
+    /// Used to move diagonally when cornering, does nothing if already on the centre line.
+    pub fn step_towards_lane_center(&mut self, dir: &Direction) {
+        let (offset_x, offset_y) = self.offset_from_tile_center();
+        match dir {
+            Direction::Up | Direction::Down => self.x -= offset_x.signum(),
+            Direction::Right | Direction::Left => self.y -= offset_y.signum(),
+        }
+    }
 }
 
 impl TilePos {
@@ -161,8 +193,8 @@ impl TilePos {
             Direction::Left => (-1, 0),
         };
 
-        let new_x = self.x as i32 + translate_x;
-        let new_y = self.y as i32 + translate_y;
+        let new_x = self.x + translate_x;
+        let new_y = self.y + translate_y;
         Self { x: new_x, y: new_y }
     }
 
@@ -174,7 +206,11 @@ impl TilePos {
 #[derive(Debug, Clone, Component, PartialEq, Eq)]
 pub enum Character {
     Pacman,
-    Ghost { name: GhostName },
+    Ghost {
+        name: GhostName,
+    },
+    /// The bonus fruit wandering through the maze, see [crate::fruit::WanderingFruit].
+    Fruit,
 }
 
 impl Display for Character {
@@ -185,7 +221,51 @@ impl Display for Character {
             match self {
                 Character::Pacman => "Pacman".to_string(),
                 Character::Ghost { name } => name.to_string(),
+                Character::Fruit => "Fruit".to_string(),
             }
         )
     }
 }
+
+/// The spritesheet and 16x16 atlas layout used for characters, icons and other sprites outside of the maze.
+#[derive(Resource, Clone)]
+pub struct CharacterSprites {
+    pub texture: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+}
+
+impl CharacterSprites {
+    pub fn sprite(&self, index: usize) -> Sprite {
+        Sprite::from_atlas_image(
+            self.texture.clone(),
+            TextureAtlas {
+                layout: self.layout.clone(),
+                index,
+            },
+        )
+    }
+}
+
+/// Loads the RON file at [path], falling back to [fallback] when it's missing or can't be parsed.
+/// [what] names what's being loaded in the logs, like "game rules".
+pub fn load_ron_or<T: DeserializeOwned>(path: &str, what: &str, fallback: impl FnOnce() -> T) -> T {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            log::info!("No {what} in {path}, using defaults");
+            return fallback();
+        }
+        Err(err) => {
+            log::warn!("Failed to read {what} from {path} ({err}), using defaults");
+            return fallback();
+        }
+    };
+
+    match ron::from_str(&contents) {
+        Ok(value) => value,
+        Err(err) => {
+            log::error!("Failed to parse {what} in {path} ({err}), using defaults");
+            fallback()
+        }
+    }
+}
@@ -1,17 +1,41 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     common::{Direction, PixelPos, TilePos},
     ghosts::{GhostName, ghost_mode::GhostMode},
+    rules::InputBuffer,
     score::Scorable,
 };
 
+/// A Pac-Man controlled by a player.
 #[derive(Component)]
-pub struct Player;
+pub struct Player {
+    /// Which player's controls steer this Pac-Man, 0 for player one.
+    pub index: usize,
+}
 
-#[derive(Component, Deref, DerefMut, PartialEq, Debug, Clone)]
+#[derive(Component, Deref, DerefMut, PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub struct Position(pub PixelPos);
 
+/// Where a character starts, it's put back here after losing a life.
+#[derive(Component)]
+pub struct SpawnPoint {
+    pub position: Position,
+    pub movable: Movable,
+}
+
+impl SpawnPoint {
+    pub fn new(position: &Position, movable: &Movable) -> Self {
+        Self {
+            position: position.clone(),
+            movable: movable.clone(),
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct Wall;
 
@@ -23,44 +47,93 @@ pub struct Ghost {
     pub ghost: GhostName,
     pub current_mode: GhostMode,
     pub corner_tile: TilePos,
+    /// The speed when neither frightened nor eaten.
+    pub base_speed: f32,
 }
 
 impl Ghost {
-    pub fn new(ghost: GhostName, corner: TilePos) -> Self {
+    pub fn new(ghost: GhostName, corner: TilePos, base_speed: f32) -> Self {
         Self {
             ghost,
             current_mode: GhostMode::Chase,
             corner_tile: corner,
+            base_speed,
         }
     }
 }
 
+/// What decides where a ghost goes at every tile.
+#[derive(Component, Debug, Default, Clone, PartialEq, Eq)]
+pub enum GhostController {
+    /// Heads for the [GhostTarget] picked by the ghost's targeting system.
+    #[default]
+    Ai,
+    /// Steered with the controls of the player with this index, in versus mode.
+    Player(usize),
+}
+
 #[derive(Component)]
 pub struct GhostDebug {
     pub ghost: GhostName,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct QueableDirection {
     pub next_direction: Option<Direction>,
+    /// What remains of the input buffer window before the queued direction is dropped.
+    pub remaining: InputBuffer,
 }
 
-#[derive(Component)]
-pub struct GhostTarget {
-    pub tile: Option<TilePos>,
-    pub should_reverse: bool,
-}
-
-impl Default for GhostTarget {
+impl Default for QueableDirection {
     fn default() -> Self {
         Self {
-            tile: None,
-            should_reverse: false,
+            next_direction: None,
+            remaining: InputBuffer::Tiles(0),
+        }
+    }
+}
+
+impl QueableDirection {
+    /// Queues [dir], restarting the input buffer window.
+    pub fn queue(&mut self, dir: Direction, window: &InputBuffer) {
+        self.next_direction = Some(dir);
+        self.remaining = window.clone();
+    }
+
+    pub fn clear(&mut self) {
+        self.next_direction = None;
+    }
+
+    /// Counts down a time based input buffer window.
+    pub fn tick(&mut self, delta: Duration) {
+        if let InputBuffer::Millis(millis) = &mut self.remaining {
+            *millis = millis.saturating_sub(delta.as_millis() as u32);
+            if *millis == 0 {
+                self.clear();
+            }
+        }
+    }
+
+    /// Counts down a tile based input buffer window.
+    pub fn tile_passed(&mut self) {
+        if let InputBuffer::Tiles(tiles) = &mut self.remaining {
+            *tiles = tiles.saturating_sub(1);
+            if *tiles == 0 {
+                self.clear();
+            }
         }
     }
 }
 
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
+pub struct GhostTarget {
+    pub tile: Option<TilePos>,
+    pub should_reverse: bool,
+    /// Turns at random like a frightened ghost, see [crate::rules::RulesProfile::MsPacMan].
+    pub wander: bool,
+}
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Movable {
     pub direction: Direction,
     /// Progress to the next pixel in the direction of travel.
@@ -100,40 +173,3 @@ impl Movable {
         }
     }
 }
-
-#[derive(Component)]
-pub struct AnimationIndices {
-    current_index: usize,
-    sprite_indices_right: Vec<usize>,
-    sprite_indices_left: Vec<usize>,
-    sprite_indices_up: Vec<usize>,
-    sprite_indices_down: Vec<usize>,
-}
-
-impl AnimationIndices {
-    pub fn new(right: Vec<usize>, left: Vec<usize>, up: Vec<usize>, down: Vec<usize>) -> Self {
-        Self {
-            current_index: 0,
-            sprite_indices_right: right,
-            sprite_indices_left: left,
-            sprite_indices_up: up,
-            sprite_indices_down: down,
-        }
-    }
-
-    pub fn next(&mut self, dir: &Direction) -> usize {
-        let curr_indices = match dir {
-            Direction::Up => &self.sprite_indices_up,
-            Direction::Right => &self.sprite_indices_right,
-            Direction::Down => &self.sprite_indices_down,
-            Direction::Left => &self.sprite_indices_left,
-        };
-
-        self.current_index = (self.current_index + 1) % curr_indices.len();
-
-        curr_indices[self.current_index]
-    }
-}
-
-#[derive(Component, Deref, DerefMut)]
-pub struct AnimationTimer(pub Timer);
@@ -0,0 +1,465 @@
+use std::str::FromStr;
+
+use bevy::{input::keyboard::KeyboardInput, log, prelude::*};
+
+use crate::{
+    animation::AnimationLibrary,
+    common::{Character, CharacterSprites, PixelPos, TilePos},
+    components::{Ghost, Movable, Player, Position},
+    death::DeathSequence,
+    debug::DebugRes,
+    events::CharacterReachedTargetEvent,
+    game_state::GameState,
+    ghosts::{
+        GhostName, ghost_bundle,
+        ghost_debug::ghost_debug_bundle,
+        ghost_mode::{GhostMode, GhostModeRes},
+    },
+    input::ActionState,
+    level::{Level, start_level},
+    lives::Lives,
+    map::MAP,
+    score::Scorable,
+    theme::Theme,
+};
+
+/// How many of the previous lines stay on screen.
+const CONSOLE_LINES: usize = 8;
+
+const HELP: &str = "teleport <who> <x> <y>, mode <chase|scatter>, spawn ghost <name>, kill [1|2], \
+level <n>, give lives <n>, set speed <who> <speed>, god <on|off>, clear dots";
+
+/// The debug console, where commands are typed in while playing.
+#[derive(Resource, Default)]
+pub struct Console {
+    open: bool,
+    input: String,
+    /// The last commands entered and their replies, oldest first.
+    lines: Vec<String>,
+    /// The command entered this frame, waiting to be run.
+    submitted: Option<String>,
+}
+
+impl Console {
+    /// Adds a line below the others, forgetting the ones that no longer fit on screen.
+    fn push_line(&mut self, line: String) {
+        self.lines.push(line);
+        let excess = self.lines.len().saturating_sub(CONSOLE_LINES);
+        self.lines.drain(..excess);
+    }
+}
+
+#[derive(Component)]
+pub struct ConsoleText;
+
+/// A character a command acts on.
+#[derive(Debug, PartialEq)]
+enum Who {
+    /// The Pac-Man steered by this player.
+    Pacman(usize),
+    Ghost(GhostName),
+}
+
+impl FromStr for Who {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pacman" | "pacman1" => Ok(Who::Pacman(0)),
+            "pacman2" => Ok(Who::Pacman(1)),
+            _ => s.parse().map(Who::Ghost),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum ConsoleCommand {
+    Help,
+    Teleport(Who, TilePos),
+    Mode(GhostMode),
+    SpawnGhost(GhostName),
+    /// The Pac-Man of this player dies on the spot.
+    Kill(usize),
+    Level(u32),
+    GiveLives(u32),
+    SetSpeed(Who, f32),
+    God(bool),
+    /// Eats every dot and energizer, clearing the level.
+    ClearDots,
+}
+
+impl FromStr for ConsoleCommand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words = s.split_whitespace().collect::<Vec<_>>();
+
+        match words.as_slice() {
+            ["help"] => Ok(Self::Help),
+            ["teleport", who, x, y] => Ok(Self::Teleport(
+                who.parse()?,
+                TilePos {
+                    x: number(x)?,
+                    y: number(y)?,
+                },
+            )),
+            ["mode", "chase"] => Ok(Self::Mode(GhostMode::Chase)),
+            ["mode", "scatter"] => Ok(Self::Mode(GhostMode::Scatter)),
+            ["mode", mode] => Err(format!(
+                "Can't switch to mode '{mode}', only chase or scatter"
+            )),
+            ["spawn", "ghost", name] => Ok(Self::SpawnGhost(name.parse()?)),
+            ["kill"] => Ok(Self::Kill(0)),
+            ["kill", player] => match number(player)? {
+                player @ (1 | 2) => Ok(Self::Kill(player - 1)),
+                player => Err(format!("There's no player {player}, only 1 or 2")),
+            },
+            ["level", level] => Ok(Self::Level(number(level)?)),
+            ["give", "lives", lives] => Ok(Self::GiveLives(number(lives)?)),
+            ["set", "speed", who, speed] => Ok(Self::SetSpeed(who.parse()?, number(speed)?)),
+            ["god", "on"] => Ok(Self::God(true)),
+            ["god", "off"] => Ok(Self::God(false)),
+            ["clear", "dots"] => Ok(Self::ClearDots),
+            _ => Err(format!("Unknown command '{s}', try 'help'")),
+        }
+    }
+}
+
+fn number<T: FromStr>(word: &str) -> Result<T, String> {
+    word.parse()
+        .map_err(|_| format!("'{word}' isn't a valid number"))
+}
+
+impl ConsoleCommand {
+    /// Runs the command, returning the reply to show in the console.
+    fn run(self, world: &mut World) -> Result<String, String> {
+        match self {
+            Self::Help => Ok(HELP.into()),
+            Self::Teleport(who, tile) => teleport(world, &who, tile),
+            Self::Mode(mode) => {
+                let reply = format!("The ghosts switch to {mode:?}");
+                world.resource_mut::<GhostModeRes>().set_global_mode(mode);
+                Ok(reply)
+            }
+            Self::SpawnGhost(name) => spawn_ghost(world, name),
+            Self::Kill(player) => kill(world, player),
+            Self::Level(level) => {
+                if level == 0 {
+                    return Err("The levels start at 1".into());
+                }
+                **world.resource_mut::<Level>() = level;
+                start_level(world);
+                Ok(format!("Now on level {level}"))
+            }
+            Self::GiveLives(lives) => {
+                let mut current = world.resource_mut::<Lives>();
+                **current = current.saturating_add(lives);
+                Ok(format!("{} lives left", **current))
+            }
+            Self::SetSpeed(who, speed) => set_speed(world, &who, speed),
+            Self::God(on) => {
+                world.resource_mut::<DebugRes>().god_mode = on;
+                Ok(format!("God mode {}", if on { "on" } else { "off" }))
+            }
+            Self::ClearDots => {
+                let mut scorables = world.query::<(Entity, &Scorable)>();
+                let dots = scorables
+                    .iter(world)
+                    .filter(|(_, scorable)| matches!(scorable, Scorable::Dot | Scorable::Energizer))
+                    .map(|(entity, _)| entity)
+                    .collect::<Vec<_>>();
+
+                for &entity in &dots {
+                    world.despawn(entity);
+                }
+                Ok(format!("Cleared {} dots", dots.len()))
+            }
+        }
+    }
+}
+
+fn find(world: &mut World, who: &Who) -> Result<Entity, String> {
+    let mut characters = world.query::<(Entity, Option<&Player>, Option<&Ghost>)>();
+    characters
+        .iter(world)
+        .find(|(_, player, ghost)| match who {
+            Who::Pacman(index) => player.is_some_and(|player| player.index == *index),
+            Who::Ghost(name) => ghost.is_some_and(|ghost| &ghost.ghost == name),
+        })
+        .map(|(entity, _, _)| entity)
+        .ok_or_else(|| match who {
+            Who::Pacman(index) => format!("There's no Pac-Man for player {}", index + 1),
+            Who::Ghost(name) => format!("{name} isn't in the maze, spawn it first"),
+        })
+}
+
+fn kill(world: &mut World, player: usize) -> Result<String, String> {
+    let dying = *world.resource::<State<GameState>>().get() == GameState::Dying
+        || matches!(
+            world.resource::<NextState<GameState>>(),
+            NextState::Pending(GameState::Dying)
+        );
+    if dying {
+        return Err("Pac-Man is already dying".into());
+    }
+
+    if world.resource::<DebugRes>().god_mode {
+        return Err("Pac-Man can't die in god mode, turn it off first".into());
+    }
+
+    let pacman = find(world, &Who::Pacman(player))?;
+    world.insert_resource(DeathSequence::new(pacman));
+    world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Dying);
+    Ok(format!("Pac-Man of player {} is caught", player + 1))
+}
+
+fn teleport(world: &mut World, who: &Who, tile: TilePos) -> Result<String, String> {
+    if MAP.is_wall(&tile) {
+        return Err(format!("({}, {}) is a wall", tile.x, tile.y));
+    }
+
+    let entity = find(world, who)?;
+    let mut character = world.entity_mut(entity);
+    if let Some(mut position) = character.get_mut::<Position>() {
+        position.0 = PixelPos::from(&tile);
+    }
+    if let Some(mut movable) = character.get_mut::<Movable>() {
+        movable.target_tile = tile.clone();
+        movable.progress = 0.;
+    }
+
+    // Standing on its target, the character picks where to go next.
+    let reply = format!("Teleported to ({}, {})", tile.x, tile.y);
+    if let Some(character) = character.get::<Character>().cloned() {
+        world.send_event(CharacterReachedTargetEvent::new(entity, character, tile));
+    }
+    Ok(reply)
+}
+
+fn spawn_ghost(world: &mut World, name: GhostName) -> Result<String, String> {
+    if find(world, &Who::Ghost(name.clone())).is_ok() {
+        return Err(format!("{name} is already in the maze"));
+    }
+
+    let sprites = world.resource::<CharacterSprites>().clone();
+    world.resource_scope(|world, animations: Mut<AnimationLibrary>| {
+        world.spawn(ghost_bundle(
+            name.clone(),
+            sprites.texture,
+            sprites.layout,
+            &animations,
+        ));
+    });
+    world.spawn(ghost_debug_bundle(name.clone()));
+
+    Ok(format!("Spawned {name}"))
+}
+
+fn set_speed(world: &mut World, who: &Who, speed: f32) -> Result<String, String> {
+    if speed <= 0. {
+        return Err("The speed has to be above 0".into());
+    }
+
+    let entity = find(world, who)?;
+    let mut character = world.entity_mut(entity);
+    // Ghosts keep their speed as a base, slowed down while frightened or sped up as eyes.
+    let speed = match character.get_mut::<Ghost>() {
+        Some(mut ghost) => {
+            ghost.base_speed = speed;
+            ghost.current_mode.speed(speed)
+        }
+        None => speed,
+    };
+    if let Some(mut movable) = character.get_mut::<Movable>() {
+        movable.speed = speed;
+    }
+
+    Ok(format!("Speed set to {speed}"))
+}
+
+pub fn setup_console(mut commands: Commands, asset_server: Res<AssetServer>, theme: Res<Theme>) {
+    commands.spawn((
+        Text::new(""),
+        ConsoleText,
+        TextFont {
+            font: asset_server.load(&theme.font),
+            font_size: 22.,
+            ..default()
+        },
+        TextColor(theme.colours.text()),
+        BackgroundColor(theme.colours.background().with_alpha(0.8)),
+        Visibility::Hidden,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(0.),
+            left: Val::Px(0.),
+            width: Val::Percent(100.),
+            padding: UiRect::all(Val::Px(8.)),
+            ..default()
+        },
+    ));
+}
+
+pub fn run_if_console_open(console: Res<Console>) -> bool {
+    console.open
+}
+
+/// Keeps the game from reacting to what's typed in the console.
+pub fn block_actions_while_typing(mut actions: ResMut<ActionState>) {
+    actions.block();
+}
+
+pub fn open_console(mut console: ResMut<Console>) {
+    console.open = true;
+}
+
+/// The commands only make sense while playing, so the console closes when play stops.
+pub fn close_console(mut console: ResMut<Console>) {
+    console.open = false;
+    console.input.clear();
+    console.submitted = None;
+}
+
+/// Types into the open console, Enter runs the command and Escape or a backtick closes it.
+pub fn type_in_console(mut console: ResMut<Console>, mut keys: EventReader<KeyboardInput>) {
+    if !console.open {
+        keys.clear();
+        return;
+    }
+
+    for key in keys.read() {
+        if !key.state.is_pressed() {
+            continue;
+        }
+
+        match key.key_code {
+            KeyCode::Escape | KeyCode::Backquote if !key.repeat => console.open = false,
+            KeyCode::Enter => {
+                let command = std::mem::take(&mut console.input);
+                if !command.trim().is_empty() {
+                    console.submitted = Some(command);
+                }
+            }
+            KeyCode::Backspace => {
+                console.input.pop();
+            }
+            _ => {
+                if let Some(text) = &key.text {
+                    console
+                        .input
+                        .extend(text.chars().filter(|c| !c.is_control() && *c != '`'));
+                }
+            }
+        }
+    }
+}
+
+pub fn run_console_command(world: &mut World) {
+    let Some(command) = world.resource_mut::<Console>().submitted.take() else {
+        return;
+    };
+
+    log::info!("Console: {command}");
+    let reply = command
+        .to_lowercase()
+        .parse::<ConsoleCommand>()
+        .and_then(|parsed| parsed.run(world))
+        .unwrap_or_else(|err| err);
+
+    let mut console = world.resource_mut::<Console>();
+    console.push_line(format!("> {command}"));
+    console.push_line(reply);
+}
+
+pub fn update_console_text(
+    console: Res<Console>,
+    text: Single<(&mut Text, &mut Visibility), With<ConsoleText>>,
+) {
+    let (mut text, mut visibility) = text.into_inner();
+    *visibility = if console.open {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+
+    let mut lines = console.lines.clone();
+    lines.push(format!("> {}_", console.input));
+    text.0 = lines.join("\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_are_parsed() {
+        assert_eq!(
+            "teleport pacman 1 1".parse(),
+            Ok(ConsoleCommand::Teleport(
+                Who::Pacman(0),
+                TilePos { x: 1, y: 1 }
+            ))
+        );
+        assert_eq!(
+            "teleport clyde -1 14".parse(),
+            Ok(ConsoleCommand::Teleport(
+                Who::Ghost(GhostName::Clyde),
+                TilePos { x: -1, y: 14 }
+            ))
+        );
+        assert_eq!(
+            "mode scatter".parse(),
+            Ok(ConsoleCommand::Mode(GhostMode::Scatter))
+        );
+        assert_eq!(
+            "spawn ghost inky".parse(),
+            Ok(ConsoleCommand::SpawnGhost(GhostName::Inky))
+        );
+        assert_eq!("kill".parse(), Ok(ConsoleCommand::Kill(0)));
+        assert_eq!("kill 2".parse(), Ok(ConsoleCommand::Kill(1)));
+        assert_eq!("level 5".parse(), Ok(ConsoleCommand::Level(5)));
+        assert_eq!("give lives 3".parse(), Ok(ConsoleCommand::GiveLives(3)));
+        assert_eq!(
+            "set speed blinky 1.05".parse(),
+            Ok(ConsoleCommand::SetSpeed(
+                Who::Ghost(GhostName::Blinky),
+                1.05
+            ))
+        );
+        assert_eq!(
+            "set speed pacman2 0.8".parse(),
+            Ok(ConsoleCommand::SetSpeed(Who::Pacman(1), 0.8))
+        );
+        assert_eq!("god on".parse(), Ok(ConsoleCommand::God(true)));
+        assert_eq!("god off".parse(), Ok(ConsoleCommand::God(false)));
+        assert_eq!("clear dots".parse(), Ok(ConsoleCommand::ClearDots));
+        assert_eq!("  help ".parse(), Ok(ConsoleCommand::Help));
+    }
+
+    #[test]
+    fn bad_commands_are_refused() {
+        for command in [
+            "",
+            "dance",
+            "kill everyone",
+            "kill 0",
+            "kill 3",
+            "mode frightened",
+            "spawn ghost sue",
+            "level five",
+            "level -1",
+            "give lives 4294967296",
+            "teleport pacman 1",
+            "teleport mspacman 1 1",
+            "set speed blinky fast",
+            "god maybe",
+        ] {
+            assert!(
+                command.parse::<ConsoleCommand>().is_err(),
+                "'{command}' should be refused"
+            );
+        }
+    }
+}
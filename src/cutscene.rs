@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use bevy::{log, prelude::*};
+use serde::Deserialize;
+
+use crate::{
+    animation::{Animation, AnimationLibrary},
+    camera::PLAYFIELD_WIDTH,
+    common::{CharacterSprites, load_ron_or},
+    game_state::GameState,
+    level::Level,
+    map::{MAP_HEIGHT, TILE_SIZE},
+    theme::Theme,
+};
+
+pub const CUTSCENES_PATH: &str = "assets/config/cutscenes.ron";
+/// Used when the cutscenes can't be loaded.
+const DEFAULT_CUTSCENES: &str = include_str!("../assets/config/cutscenes.ron");
+
+/// The big Pac-Man (32x32) and the naked Blinky dragging his sheet (32x16) don't fit in the grid,
+/// they're added to the atlas layout after the wide points sprites, starting at index 186.
+const CUTSCENE_SPRITES: [(UVec2, UVec2); 5] = [
+    (UVec2::new(488, 16), UVec2::new(32, 32)),
+    (UVec2::new(520, 16), UVec2::new(32, 32)),
+    (UVec2::new(552, 16), UVec2::new(32, 32)),
+    (UVec2::new(584, 128), UVec2::new(32, 16)),
+    (UVec2::new(616, 128), UVec2::new(32, 16)),
+];
+
+/// Covers the maze while a cutscene plays.
+const BACKDROP_Z: f32 = 2.;
+const ACTOR_Z: f32 = 3.;
+
+/// Adds the sprites only used in cutscenes to the character atlas [layout].
+pub fn add_cutscene_sprites(layout: &mut TextureAtlasLayout) {
+    for (min, size) in CUTSCENE_SPRITES {
+        layout.add_texture(URect::from_corners(min, min + size));
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Keyframe {
+    /// Seconds since the start of the cutscene.
+    pub time: f32,
+    /// Where the sprite is centred, in maze pixels with y pointing down.
+    pub position: (f32, f32),
+    /// The animation clip to switch to from here on.
+    #[serde(default)]
+    pub clip: Option<String>,
+}
+
+/// The movements of a single sprite.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Track {
+    /// The character in the [AnimationLibrary] the clips belong to.
+    pub character: String,
+    /// Sorted by time.
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl Track {
+    /// Where the sprite is at [time] and the clip it should be playing, or None if it's not on
+    /// screen.
+    fn sample(&self, time: f32) -> Option<(Vec2, Option<&str>)> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+        if time < first.time || time > last.time {
+            return None;
+        }
+
+        let clip = self
+            .keyframes
+            .iter()
+            .take_while(|k| k.time <= time)
+            .filter_map(|k| k.clip.as_deref())
+            .last();
+
+        let next = self
+            .keyframes
+            .iter()
+            .position(|k| k.time > time)
+            .unwrap_or(self.keyframes.len() - 1);
+        let from = &self.keyframes[next.saturating_sub(1)];
+        let to = &self.keyframes[next];
+
+        let progress = if to.time > from.time {
+            ((time - from.time) / (to.time - from.time)).clamp(0., 1.)
+        } else {
+            1.
+        };
+        let position = Vec2::from(from.position).lerp(Vec2::from(to.position), progress);
+
+        Some((position, clip))
+    }
+
+    fn initial_clip(&self) -> &str {
+        self.keyframes
+            .iter()
+            .find_map(|k| k.clip.as_deref())
+            .unwrap_or("right")
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Cutscene {
+    /// Seconds until the game continues.
+    pub duration: f32,
+    pub tracks: Vec<Track>,
+}
+
+/// The scripted cutscenes, see `assets/config/cutscenes.ron`.
+#[derive(Resource, Deserialize)]
+pub struct Cutscenes {
+    /// The cutscene played after finishing a level.
+    intermissions: HashMap<u32, String>,
+    cutscenes: HashMap<String, Cutscene>,
+}
+
+impl Cutscenes {
+    pub fn load_or_default(path: &str) -> Self {
+        load_ron_or(path, "cutscenes", Self::default)
+    }
+
+    /// The intermission played after finishing [level], if any.
+    pub fn intermission_after(&self, level: u32) -> Option<&Cutscene> {
+        let name = self.intermissions.get(&level)?;
+        let cutscene = self.cutscenes.get(name);
+        if cutscene.is_none() {
+            log::error!("No cutscene named {name}");
+        }
+
+        cutscene
+    }
+}
+
+impl Default for Cutscenes {
+    fn default() -> Self {
+        ron::from_str(DEFAULT_CUTSCENES).expect("The default cutscenes should be valid")
+    }
+}
+
+/// The cutscene currently playing.
+#[derive(Resource)]
+pub struct CutscenePlayer {
+    cutscene: Cutscene,
+    elapsed: f32,
+}
+
+/// A sprite moved by the track at [CutsceneActor::0] of the playing cutscene.
+#[derive(Component)]
+pub struct CutsceneActor(usize);
+
+fn display_pos(position: Vec2) -> Vec3 {
+    Vec3::new(position.x, -position.y, ACTOR_Z)
+}
+
+/// Sets up the intermission after the level that was just finished.
+pub fn start_intermission(
+    mut commands: Commands,
+    cutscenes: Res<Cutscenes>,
+    level: Res<Level>,
+    sprites: Res<CharacterSprites>,
+    animations: Res<AnimationLibrary>,
+    theme: Res<Theme>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(cutscene) = cutscenes.intermission_after(**level) else {
+        next_state.set(GameState::Playing);
+        return;
+    };
+
+    let maze_height = (MAP_HEIGHT as i32 * TILE_SIZE) as f32;
+    commands.spawn((
+        StateScoped(GameState::Intermission),
+        Sprite::from_color(
+            theme.colours.background(),
+            Vec2::new(PLAYFIELD_WIDTH, maze_height),
+        ),
+        Transform::from_xyz(PLAYFIELD_WIDTH / 2., -maze_height / 2., BACKDROP_Z),
+    ));
+
+    for (i, track) in cutscene.tracks.iter().enumerate() {
+        let animation = animations.animation(&track.character, track.initial_clip());
+        let position = track.sample(0.).map(|(p, _)| p).unwrap_or_default();
+
+        commands.spawn((
+            StateScoped(GameState::Intermission),
+            sprites.sprite(animation.index()),
+            animation,
+            CutsceneActor(i),
+            Transform::from_translation(display_pos(position)),
+            Visibility::Hidden,
+        ));
+    }
+
+    commands.insert_resource(CutscenePlayer {
+        cutscene: cutscene.clone(),
+        elapsed: 0.,
+    });
+}
+
+/// Moves the actors along their tracks, continuing with the game when the cutscene is over.
+pub fn play_cutscene(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player: ResMut<CutscenePlayer>,
+    actors: Query<(
+        &CutsceneActor,
+        &mut Animation,
+        &mut Transform,
+        &mut Visibility,
+    )>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    player.elapsed += time.delta_secs();
+
+    for (actor, mut animation, mut transform, mut visibility) in actors {
+        let Some(track) = player.cutscene.tracks.get(actor.0) else {
+            continue;
+        };
+
+        match track.sample(player.elapsed) {
+            Some((position, clip)) => {
+                transform.translation = display_pos(position);
+                *visibility = Visibility::Inherited;
+                if let Some(clip) = clip {
+                    animation.switch(clip);
+                }
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+
+    if player.elapsed >= player.cutscene.duration {
+        commands.remove_resource::<CutscenePlayer>();
+        next_state.set(GameState::Playing);
+    }
+}
@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+
+use crate::{
+    animation::Animation,
+    attract::Demo,
+    common::TilePos,
+    components::{Ghost, GhostTarget, Movable, Player, Position, QueableDirection, SpawnPoint},
+    debug::DebugRes,
+    events::{AnimationFinishedEvent, GameOverEvent, PacmanDeathEvent},
+    fruit::BonusFruit,
+    game_state::GameState,
+    ghosts::ghost_mode::{FrightenedRes, GhostMode, GhostModeRes},
+    level::Level,
+    lives::Lives,
+    player::DEATH_CLIP,
+    players::Players,
+    rng::GameRng,
+};
+
+/// How long everything stands still after Pac-Man is caught, before the death clip plays.
+const FREEZE_SECONDS: f32 = 1.;
+/// How long the maze stays empty after the death clip, before play resumes.
+const AFTERMATH_SECONDS: f32 = 1.;
+
+#[derive(Debug, PartialEq, Eq)]
+enum DeathStage {
+    Freeze,
+    Shrivel,
+    Aftermath,
+    /// Ready to lose the life.
+    Done,
+}
+
+#[derive(Resource)]
+pub struct DeathSequence {
+    stage: DeathStage,
+    timer: Timer,
+    /// The Pac-Man that was caught, in co-op the other one just disappears with the ghosts.
+    pacman: Entity,
+}
+
+impl DeathSequence {
+    pub fn new(pacman: Entity) -> Self {
+        Self {
+            stage: DeathStage::Freeze,
+            timer: Timer::from_seconds(FREEZE_SECONDS, TimerMode::Once),
+            pacman,
+        }
+    }
+}
+
+/// Pac-Man dies when sharing a tile with a ghost that's neither frightened nor eaten.
+pub fn catch_pacman(
+    mut commands: Commands,
+    pacmen: Query<(Entity, &Position), With<Player>>,
+    ghosts: Query<(&Position, &Ghost), Without<Player>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    debug: Res<DebugRes>,
+) {
+    if debug.god_mode {
+        return;
+    }
+
+    let threats: Vec<TilePos> = ghosts
+        .iter()
+        .filter(|(_, ghost)| matches!(ghost.current_mode, GhostMode::Chase | GhostMode::Scatter))
+        .map(|(position, _)| (&position.0).into())
+        .collect();
+
+    let caught = pacmen
+        .iter()
+        .find(|(_, position)| threats.contains(&TilePos::from(&position.0)));
+
+    if let Some((pacman, _)) = caught {
+        commands.insert_resource(DeathSequence::new(pacman));
+        next_state.set(GameState::Dying);
+    }
+}
+
+/// Only animates while the death clip is playing, everything stands still otherwise.
+pub fn run_if_shrivelling(sequence: Res<DeathSequence>) -> bool {
+    sequence.stage == DeathStage::Shrivel
+}
+
+pub fn update_death_sequence(
+    time: Res<Time>,
+    mut sequence: ResMut<DeathSequence>,
+    mut animation_events: EventReader<AnimationFinishedEvent>,
+    mut pacmen: Query<(Entity, &mut Animation, &mut Visibility), With<Player>>,
+    mut ghosts: Query<&mut Visibility, (With<Ghost>, Without<Player>)>,
+    mut death_events: EventWriter<PacmanDeathEvent>,
+) {
+    let pacman = sequence.pacman;
+
+    if sequence.stage == DeathStage::Freeze {
+        if !sequence.timer.tick(time.delta()).finished() {
+            return;
+        }
+
+        for mut visibility in &mut ghosts {
+            *visibility = Visibility::Hidden;
+        }
+        for (entity, _, mut visibility) in &mut pacmen {
+            if entity != pacman {
+                *visibility = Visibility::Hidden;
+            }
+        }
+    }
+
+    let Ok((_, mut animation, mut pacman_visibility)) = pacmen.get_mut(pacman) else {
+        return;
+    };
+
+    match sequence.stage {
+        DeathStage::Freeze => {
+            animation.play(DEATH_CLIP);
+            death_events.write(PacmanDeathEvent);
+            sequence.stage = DeathStage::Shrivel;
+        }
+        DeathStage::Shrivel => {
+            let finished = animation_events
+                .read()
+                .filter(|e| e.entity == pacman && e.clip == DEATH_CLIP)
+                .count()
+                > 0;
+
+            if finished {
+                *pacman_visibility = Visibility::Hidden;
+                sequence.stage = DeathStage::Aftermath;
+                sequence.timer = Timer::from_seconds(AFTERMATH_SECONDS, TimerMode::Once);
+            }
+        }
+        DeathStage::Aftermath => {
+            if sequence.timer.tick(time.delta()).finished() {
+                animation.stop();
+                sequence.stage = DeathStage::Done;
+            }
+        }
+        DeathStage::Done => {}
+    }
+}
+
+pub fn lose_life(
+    sequence: Res<DeathSequence>,
+    demo: Option<Res<Demo>>,
+    mut lives: ResMut<Lives>,
+    mut players: ResMut<Players>,
+    mut game_over_events: EventWriter<GameOverEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if sequence.stage != DeathStage::Done {
+        return;
+    }
+
+    if demo.is_some() {
+        // The demo is over, the life wasn't the player's to lose.
+        next_state.set(GameState::Title);
+        return;
+    }
+
+    **lives = lives.saturating_sub(1);
+    if players.next_player().is_some() {
+        players.end_turn();
+        next_state.set(GameState::Playing);
+    } else if **lives == 0 {
+        game_over_events.write(GameOverEvent);
+    } else {
+        next_state.set(GameState::Playing);
+    }
+}
+
+/// Puts everyone back where they started for the next life, the eaten dots stay eaten.
+pub fn reset_positions(
+    mut characters: Query<(
+        &SpawnPoint,
+        &mut Position,
+        &mut Movable,
+        &mut Visibility,
+        Option<&mut QueableDirection>,
+    )>,
+) {
+    for (spawn, mut position, mut movable, mut visibility, queued_dir) in &mut characters {
+        *position = spawn.position.clone();
+        *movable = spawn.movable.clone();
+        *visibility = Visibility::Inherited;
+
+        if let Some(mut queued_dir) = queued_dir {
+            queued_dir.clear();
+        }
+    }
+}
+
+/// Calms the ghosts down, starts their scatter and chase schedule over and clears the bonus fruit
+/// for the next life.
+pub fn reset_ghosts(
+    mut commands: Commands,
+    ghosts: Query<(&mut Ghost, &mut GhostTarget, &mut Movable)>,
+    fruits: Query<Entity, With<BonusFruit>>,
+    level: Res<Level>,
+    mut mode_res: ResMut<GhostModeRes>,
+    mut frightened: ResMut<FrightenedRes>,
+    mut rng: ResMut<GameRng>,
+) {
+    frightened.timer = None;
+    *mode_res = GhostModeRes::new(**level);
+    rng.restart_life();
+
+    for (mut ghost, mut target, mut movable) in ghosts {
+        ghost.current_mode = mode_res.global_mode.clone();
+        movable.speed = ghost.current_mode.speed(ghost.base_speed);
+        *target = GhostTarget::default();
+    }
+
+    for fruit in &fruits {
+        commands.entity(fruit).despawn();
+    }
+}
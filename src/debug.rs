@@ -1,25 +1,51 @@
 use bevy::prelude::*;
 
-use crate::{components::GhostDebug, ui::DebugText};
+use crate::{
+    components::GhostDebug,
+    input::{Action, ActionState},
+    ui::DebugText,
+};
+
+/// The simulation speeds to pick from in debug mode.
+const SIMULATION_SPEEDS: [f32; 5] = [0.25, 0.5, 1., 2., 8.];
+const NORMAL_SPEED: usize = 2;
 
 #[derive(Resource)]
 pub struct DebugRes {
     pub debug_mode: bool,
+    /// Pac-Man can't be caught, set from the console.
+    pub god_mode: bool,
+    /// Index into [SIMULATION_SPEEDS].
+    speed: usize,
+    /// Whether to advance the frozen simulation by one fixed frame next frame.
+    step_frame: bool,
+}
+
+impl DebugRes {
+    pub fn simulation_speed(&self) -> f32 {
+        SIMULATION_SPEEDS[self.speed]
+    }
 }
 
 impl Default for DebugRes {
     fn default() -> Self {
-        Self { debug_mode: false }
+        Self {
+            debug_mode: false,
+            god_mode: false,
+            speed: NORMAL_SPEED,
+            step_frame: false,
+        }
     }
 }
 
 pub fn toggle_debug_mode(
     mut debug: ResMut<DebugRes>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     ghost_debug_query: Query<&mut Transform, With<GhostDebug>>,
     mut debug_text: Single<&mut Text, With<DebugText>>,
+    mut time: ResMut<Time<Virtual>>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::KeyT) {
+    if actions.just_pressed(Action::ToggleDebug) {
         debug.debug_mode = !debug.debug_mode;
 
         if !debug.debug_mode {
@@ -27,10 +53,67 @@ pub fn toggle_debug_mode(
             for mut transform in ghost_debug_query {
                 transform.translation = Vec3::new(-1000., -1000., 0.);
             }
+
+            // Back to normal play.
+            debug.god_mode = false;
+            debug.speed = NORMAL_SPEED;
+            time.set_relative_speed(debug.simulation_speed());
+            time.unpause();
         }
     }
 }
 
+/// Freezes, steps and changes the speed of the simulation.
+pub fn control_simulation(
+    mut debug: ResMut<DebugRes>,
+    actions: Res<ActionState>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if actions.just_pressed(Action::FreezeSimulation) {
+        if time.is_paused() {
+            time.unpause();
+        } else {
+            time.pause();
+        }
+    }
+
+    if actions.just_pressed(Action::StepFrame) {
+        time.pause();
+        debug.step_frame = true;
+    }
+
+    if actions.just_pressed(Action::SlowDown) {
+        debug.speed = debug.speed.saturating_sub(1);
+    }
+    if actions.just_pressed(Action::SpeedUp) {
+        debug.speed = (debug.speed + 1).min(SIMULATION_SPEEDS.len() - 1);
+    }
+    time.set_relative_speed(debug.simulation_speed());
+}
+
+/// Moves the frozen clock forward by exactly one fixed timestep when a step was asked for, so
+/// both the fixed and the per-frame systems run one frame's worth.
+pub fn step_simulation(
+    mut debug: ResMut<DebugRes>,
+    fixed: Res<Time<Fixed>>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut time: ResMut<Time>,
+) {
+    if !debug.step_frame {
+        return;
+    }
+
+    debug.step_frame = false;
+    virtual_time.advance_by(fixed.timestep());
+    *time = virtual_time.as_generic();
+}
+
+/// Whether the simulation moved on this frame, which it only does on the stepped frames while
+/// frozen.
+pub fn run_if_simulation_advancing(time: Res<Time<Virtual>>) -> bool {
+    !time.delta().is_zero()
+}
+
 pub fn run_if_debug(debug: Res<DebugRes>) -> bool {
     debug.debug_mode
 }
@@ -1,19 +1,25 @@
 use bevy::prelude::*;
 
 use crate::{
-    common::{Character, TilePos},
+    common::{Character, PixelPos, TilePos},
     ghosts::GhostName,
+    score::{Milestone, Scorable},
 };
 
 #[derive(Event)]
 pub struct CharacterReachedTargetEvent {
+    pub entity: Entity,
     pub character: Character,
     pub tile: TilePos,
 }
 
 impl CharacterReachedTargetEvent {
-    pub fn new(character: Character, tile: TilePos) -> Self {
-        CharacterReachedTargetEvent { character, tile }
+    pub fn new(entity: Entity, character: Character, tile: TilePos) -> Self {
+        CharacterReachedTargetEvent {
+            entity,
+            character,
+            tile,
+        }
     }
 
     pub fn is_pacman(&self) -> bool {
@@ -48,3 +54,35 @@ impl CharacterReachedTargetEvent {
             }
     }
 }
+
+/// Sent when Pac-Man starts shrivelling up after being caught.
+#[derive(Event)]
+pub struct PacmanDeathEvent;
+
+/// Sent when the last life is lost.
+#[derive(Event)]
+pub struct GameOverEvent;
+
+/// Sent once whenever the score passes a threshold of a registered milestone.
+#[derive(Event)]
+pub struct ScoreMilestoneEvent {
+    pub milestone: Milestone,
+    pub threshold: u32,
+}
+
+/// Sent when Pac-Man eats something worth points.
+#[derive(Event)]
+pub struct ScorableEatenEvent {
+    /// The eaten entity, despawned already unless it's a ghost.
+    pub entity: Entity,
+    pub scorable: Scorable,
+    pub position: PixelPos,
+    pub points: u32,
+}
+
+/// Sent when a one-shot animation clip has played its last frame.
+#[derive(Event)]
+pub struct AnimationFinishedEvent {
+    pub entity: Entity,
+    pub clip: String,
+}
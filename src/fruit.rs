@@ -0,0 +1,292 @@
+use bevy::{prelude::*, sprite::Anchor};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{Character, CharacterSprites, Direction, PixelPos, TilePos},
+    components::{Movable, Position},
+    events::ScorableEatenEvent,
+    ghosts::ghost_movement::next_tile,
+    level::Level,
+    map::{HALF_TILE_SIZE, MAP, TILE_SIZE},
+    rng::GameRng,
+    rules::GameRules,
+    score::Scorable,
+};
+
+/// The amount of eaten dots (including energizers) at which a bonus fruit appears.
+const BONUS_FRUIT_DOT_COUNTS: [u32; 2] = [70, 170];
+const BONUS_FRUIT_SECONDS: f32 = 9.5;
+/// Only there so a wandering fruit stuck circling can't stay forever, it normally leaves
+/// through a tunnel well before.
+const WANDERING_FRUIT_SECONDS: f32 = 30.;
+const WANDERING_FRUIT_SPEED: f32 = 0.4;
+/// The wandering fruit loops around the ghost house, passing below and then above it.
+const WANDERING_FRUIT_LOOP: [TilePos; 2] = [TilePos { x: 14, y: 17 }, TilePos { x: 13, y: 11 }];
+
+/// The amount of sprites in the 16x16 grid of the character atlas layout.
+const ATLAS_GRID_SPRITES: usize = 14 * 13;
+/// The points shown for fruit worth 1000 or more are wider than a 16x16 sprite, so they're
+/// added to the atlas layout after the grid. They're stacked in a column in the spritesheet.
+const WIDE_POINTS_TOP_LEFT: UVec2 = UVec2::new(518, 144);
+const WIDE_POINTS_SIZE: UVec2 = UVec2::new(20, 16);
+
+/// Adds the wide fruit points sprites (1000, 2000, 3000 and 5000) to the character atlas [layout].
+pub fn add_wide_points_sprites(layout: &mut TextureAtlasLayout) {
+    for i in 0..4 {
+        let min = WIDE_POINTS_TOP_LEFT + UVec2::new(0, i * WIDE_POINTS_SIZE.y);
+        layout.add_texture(URect::from_corners(min, min + WIDE_POINTS_SIZE));
+    }
+}
+
+/// The bonus fruit (and other symbols) appearing in the maze, one kind per level.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Fruit {
+    Cherry,
+    Strawberry,
+    Orange,
+    Apple,
+    Melon,
+    Galaxian,
+    Bell,
+    Key,
+}
+
+impl Fruit {
+    pub fn for_level(level: u32) -> Self {
+        match level {
+            0 | 1 => Fruit::Cherry,
+            2 => Fruit::Strawberry,
+            3 | 4 => Fruit::Orange,
+            5 | 6 => Fruit::Apple,
+            7 | 8 => Fruit::Melon,
+            9 | 10 => Fruit::Galaxian,
+            11 | 12 => Fruit::Bell,
+            _ => Fruit::Key,
+        }
+    }
+
+    pub fn points(&self) -> u32 {
+        match self {
+            Fruit::Cherry => 100,
+            Fruit::Strawberry => 300,
+            Fruit::Orange => 500,
+            Fruit::Apple => 700,
+            Fruit::Melon => 1000,
+            Fruit::Galaxian => 2000,
+            Fruit::Bell => 3000,
+            Fruit::Key => 5000,
+        }
+    }
+
+    pub fn sprite_index(&self) -> usize {
+        match self {
+            Fruit::Cherry => 44,
+            Fruit::Strawberry => 45,
+            Fruit::Orange => 46,
+            Fruit::Apple => 47,
+            Fruit::Melon => 48,
+            Fruit::Galaxian => 49,
+            Fruit::Bell => 50,
+            Fruit::Key => 51,
+        }
+    }
+
+    /// The sprite showing the points this fruit is worth.
+    pub fn points_sprite_index(&self) -> usize {
+        match self {
+            Fruit::Cherry => 126,
+            Fruit::Strawberry => 127,
+            Fruit::Orange => 128,
+            Fruit::Apple => 129,
+            Fruit::Melon => ATLAS_GRID_SPRITES,
+            Fruit::Galaxian => ATLAS_GRID_SPRITES + 1,
+            Fruit::Bell => ATLAS_GRID_SPRITES + 2,
+            Fruit::Key => ATLAS_GRID_SPRITES + 3,
+        }
+    }
+}
+
+/// The dots (including energizers) eaten so far this level.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct DotsEaten(pub u32);
+
+/// A fruit in the maze below the ghost house, only there for a limited time.
+#[derive(Component)]
+pub struct BonusFruit {
+    pub timer: Timer,
+}
+
+/// A bonus fruit entering the maze through a tunnel, heading for [WanderingFruit::waypoints] in
+/// turn and leaving through a tunnel again.
+#[derive(Component)]
+pub struct WanderingFruit {
+    pub waypoints: Vec<TilePos>,
+}
+
+pub fn spawn_bonus_fruit(
+    mut commands: Commands,
+    sprites: Res<CharacterSprites>,
+    level: Res<Level>,
+    rules: Res<GameRules>,
+    mut rng: ResMut<GameRng>,
+    mut dots_eaten: ResMut<DotsEaten>,
+    mut eaten_events: EventReader<ScorableEatenEvent>,
+) {
+    for event in eaten_events.read() {
+        if !matches!(event.scorable, Scorable::Dot | Scorable::Energizer) {
+            continue;
+        }
+
+        **dots_eaten += 1;
+        if !BONUS_FRUIT_DOT_COUNTS.contains(&dots_eaten) {
+            continue;
+        }
+
+        let fruit = Fruit::for_level(**level);
+        let mut sprite = sprites.sprite(fruit.sprite_index());
+        sprite.anchor = Anchor::TopLeft;
+
+        if rules.profile.wandering_fruit() {
+            commands.spawn((sprite, wandering_fruit(&mut rng), Scorable::Fruit(fruit)));
+            continue;
+        }
+
+        // Right between the two tiles below the ghost house.
+        let position = PixelPos {
+            x: 14 * TILE_SIZE,
+            y: 17 * TILE_SIZE + HALF_TILE_SIZE,
+        };
+
+        commands.spawn((
+            sprite,
+            BonusFruit {
+                timer: Timer::from_seconds(BONUS_FRUIT_SECONDS, TimerMode::Once),
+            },
+            Transform::from_translation(position.to_character_display_pos()),
+            Position(position),
+            Scorable::Fruit(fruit),
+        ));
+    }
+}
+
+/// The movement of a fruit entering through a random tunnel and leaving through another.
+fn wandering_fruit(rng: &mut GameRng) -> impl Bundle {
+    let (entrance, direction) = if rng.below(2) == 0 {
+        (MAP.left_tp_position(), Direction::Right)
+    } else {
+        (MAP.right_tp_position(), Direction::Left)
+    };
+    let exit = MAP.get_tp_positions()[rng.below(2) as usize].clone();
+
+    let position: PixelPos = (&entrance).into();
+    let movable = Movable::new(
+        entrance.translate(&direction),
+        direction,
+        WANDERING_FRUIT_SPEED,
+    );
+
+    let mut waypoints = WANDERING_FRUIT_LOOP.to_vec();
+    waypoints.push(exit);
+
+    (
+        BonusFruit {
+            timer: Timer::from_seconds(WANDERING_FRUIT_SECONDS, TimerMode::Once),
+        },
+        WanderingFruit { waypoints },
+        Character::Fruit,
+        Transform::from_translation(position.to_character_display_pos()),
+        Position(position),
+        movable,
+    )
+}
+
+/// Steers the wandering fruit towards its next waypoint, it's gone once past the last one.
+pub fn steer_wandering_fruit(
+    mut commands: Commands,
+    fruits: Query<(Entity, &Position, &mut Movable, &mut WanderingFruit)>,
+) {
+    for (entity, position, mut movable, mut fruit) in fruits {
+        let tile: TilePos = (&position.0).into();
+        if tile != movable.target_tile || !position.in_middle_of_tile() {
+            continue;
+        }
+
+        if fruit.waypoints.first() == Some(&tile) {
+            fruit.waypoints.remove(0);
+        }
+
+        let Some(waypoint) = fruit.waypoints.first() else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        if let Some((next, direction)) = next_tile(&tile, &movable.direction, waypoint) {
+            movable.target_tile = next;
+            movable.direction = direction;
+        }
+    }
+}
+
+pub fn despawn_bonus_fruit(
+    mut commands: Commands,
+    time: Res<Time>,
+    fruits: Query<(Entity, &mut BonusFruit)>,
+) {
+    for (entity, mut fruit) in fruits {
+        fruit.timer.tick(time.delta());
+        if fruit.timer.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{events::CharacterReachedTargetEvent, movement::move_character};
+
+    #[test]
+    fn wandering_fruit_loops_around_the_ghost_house_and_leaves() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Events<CharacterReachedTargetEvent>>();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((move_character, steer_wandering_fruit).chain());
+
+        for seed in 1..=4 {
+            let mut rng = GameRng::from_seed(seed);
+            let fruit = world.spawn(wandering_fruit(&mut rng)).id();
+
+            let mut visited = Vec::new();
+            // A minute of frames, the safety timer would have removed it after 30 seconds.
+            for _ in 0..60 * 60 {
+                let Ok(position) = world.query::<&Position>().get(&world, fruit) else {
+                    break;
+                };
+                let tile = TilePos::from(&position.0);
+                if visited.last() != Some(&tile) {
+                    visited.push(tile);
+                }
+
+                world
+                    .resource_mut::<Time>()
+                    .advance_by(Duration::from_secs_f32(1. / 60.));
+                schedule.run(&mut world);
+            }
+
+            assert!(
+                world.get_entity(fruit).is_err(),
+                "The fruit should leave the maze (seed {seed})"
+            );
+            for waypoint in WANDERING_FRUIT_LOOP {
+                assert!(
+                    visited.contains(&waypoint),
+                    "The fruit should pass {waypoint:?} (seed {seed})"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use bevy::prelude::*;
+
+#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameState {
+    /// The attract loop: the title screen, alternating with a demo game.
+    #[default]
+    Title,
+    /// A game is being played, or the demo while [crate::attract::Demo] exists.
+    Playing,
+    /// Pac-Man was caught by a ghost, the death sequence is playing.
+    Dying,
+    /// Every dot was eaten, the cleared maze is shown before the next level.
+    LevelComplete,
+    /// A cutscene plays between levels.
+    Intermission,
+    /// A new high score was set and the player is entering their initials.
+    EnterInitials,
+    GameOver,
+}
@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+
+use crate::{
+    common::{Direction, TilePos},
+    components::{Ghost, GhostTarget, Movable, Player, Position},
+    debug::DebugRes,
+    ghosts::{GhostName, ghost_mode::GhostMode},
+    rules::{CoopTargeting, GameRules},
+};
+
+/// A Pac-Man as the ghosts see him.
+pub struct PacmanView {
+    /// The player steering him, see [Player::index].
+    pub player: usize,
+    pub tile: TilePos,
+    pub direction: Direction,
+}
+
+/// A ghost as the ghosts see it.
+pub struct GhostView {
+    pub name: GhostName,
+    pub tile: TilePos,
+    pub corner_tile: TilePos,
+}
+
+/// Everything a [GhostBrain] may look at when picking a target, gathered once per frame.
+pub struct WorldSnapshot<'a> {
+    pub pacmen: Vec<PacmanView>,
+    pub ghosts: Vec<GhostView>,
+    pub rules: &'a GameRules,
+}
+
+impl<'a> WorldSnapshot<'a> {
+    fn gather<'w>(
+        rules: &'a GameRules,
+        pacmen: impl Iterator<Item = (&'w Player, &'w Position, &'w Movable)>,
+        ghosts: impl Iterator<Item = (&'w Ghost, &'w Position)>,
+    ) -> Self {
+        Self {
+            pacmen: pacmen
+                .map(|(player, position, movable)| PacmanView {
+                    player: player.index,
+                    tile: (&position.0).into(),
+                    direction: movable.direction.clone(),
+                })
+                .collect(),
+            ghosts: ghosts
+                .map(|(ghost, position)| GhostView {
+                    name: ghost.ghost.clone(),
+                    tile: (&position.0).into(),
+                    corner_tile: ghost.corner_tile.clone(),
+                })
+                .collect(),
+            rules,
+        }
+    }
+
+    pub fn ghost(&self, name: &GhostName) -> Option<&GhostView> {
+        self.ghosts.iter().find(|ghost| &ghost.name == name)
+    }
+
+    /// The Pac-Man [me] chases, picked by [GameRules::coop_targeting] when there's more than one.
+    pub fn chased_pacman(&self, me: &GhostView) -> Option<&PacmanView> {
+        let assigned = self.pacmen.iter().find(|pacman| {
+            self.rules.coop_targeting == CoopTargeting::Split
+                && pacman.player == me.name.assigned_player()
+        });
+
+        assigned.or_else(|| {
+            self.pacmen.iter().min_by(|a, b| {
+                a.tile
+                    .dist_to(&me.tile)
+                    .total_cmp(&b.tile.dist_to(&me.tile))
+            })
+        })
+    }
+}
+
+/// A ghost personality, deciding where the ghost heads for while chasing.
+pub trait GhostBrain: Send + Sync + 'static {
+    /// The tile [me] heads for, None keeps the current target.
+    fn chase_target(&self, me: &GhostView, world: &WorldSnapshot) -> Option<TilePos>;
+
+    /// Shows how the target was picked, in debug mode.
+    fn draw_debug(&self, _me: &GhostView, _world: &WorldSnapshot, _gizmos: &mut Gizmos) {}
+}
+
+/// The personality of a ghost, see [GhostBrain].
+#[derive(Component)]
+pub struct Brain(Box<dyn GhostBrain>);
+
+impl Brain {
+    pub fn new(brain: impl GhostBrain) -> Self {
+        Self(Box::new(brain))
+    }
+}
+
+/// Lets every chasing ghost's [Brain] pick its target.
+pub fn update_ghost_targets(
+    rules: Res<GameRules>,
+    pacmen: Query<(&Player, &Position, &Movable)>,
+    ghosts: Query<(&Ghost, &Position)>,
+    brains: Query<(&Brain, &Ghost, &mut GhostTarget)>,
+) {
+    let world = WorldSnapshot::gather(&rules, pacmen.iter(), ghosts.iter());
+
+    for (brain, ghost, mut target) in brains {
+        if ghost.current_mode != GhostMode::Chase {
+            continue;
+        }
+
+        let Some(me) = world.ghost(&ghost.ghost) else {
+            continue;
+        };
+
+        if let Some(tile) = brain.0.chase_target(me, &world) {
+            target.tile = Some(tile);
+        }
+    }
+}
+
+pub fn debug_ghost_brains(
+    debug_mode: Res<DebugRes>,
+    rules: Res<GameRules>,
+    pacmen: Query<(&Player, &Position, &Movable)>,
+    ghosts: Query<(&Ghost, &Position)>,
+    brains: Query<(&Brain, &Ghost)>,
+    mut gizmos: Gizmos,
+) {
+    if !debug_mode.debug_mode {
+        return;
+    }
+
+    let world = WorldSnapshot::gather(&rules, pacmen.iter(), ghosts.iter());
+
+    for (brain, ghost) in brains {
+        if ghost.current_mode != GhostMode::Chase {
+            continue;
+        }
+
+        if let Some(me) = world.ghost(&ghost.ghost) {
+            brain.0.draw_debug(me, &world, &mut gizmos);
+        }
+    }
+}
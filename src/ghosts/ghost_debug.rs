@@ -5,6 +5,7 @@ use crate::{
     components::{Ghost, GhostDebug, GhostTarget, Movable, Position},
     debug::DebugRes,
     ghosts::{GhostName, ghost_mode::GhostModeRes, ghost_movement::next_tile},
+    input::{Action, ActionState},
     map::TILE_SIZE,
 };
 
@@ -113,13 +114,14 @@ fn estimate_ghost_path(
 pub fn ghost_mode_debug_update(
     mut mode: ResMut<GhostModeRes>,
     debug_mode: Res<DebugRes>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
 ) {
     if !debug_mode.debug_mode {
         return;
     }
 
-    if keyboard_input.just_pressed(KeyCode::KeyH) {
-        mode.global_mode = mode.global_mode.next();
+    if actions.just_pressed(Action::CycleGhostMode) {
+        let next = mode.global_mode.next();
+        mode.set_global_mode(next);
     }
 }
@@ -1,9 +1,26 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+use crate::{
+    components::{Ghost, GhostTarget, Movable},
+    events::ScorableEatenEvent,
+    level::Level,
+    score::{Scorable, Score},
+};
+
+const FRIGHTENED_SPEED: f32 = 0.5;
+const EATEN_SPEED: f32 = 1.5;
+/// How long before the end of frightened mode the ghosts start flashing.
+pub const FRIGHTENED_FLASH_SECONDS: f32 = 2.;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GhostMode {
     Chase,
     Scatter,
+    /// Blue and running away after Pac-Man ate an energizer.
+    Frightened,
+    /// Eaten by Pac-Man, the eyes are returning to the ghost house.
+    Eaten,
 }
 
 impl GhostMode {
@@ -11,19 +28,232 @@ impl GhostMode {
         match self {
             GhostMode::Chase => Self::Scatter,
             GhostMode::Scatter => Self::Chase,
+            GhostMode::Frightened | GhostMode::Eaten => Self::Chase,
+        }
+    }
+
+    pub fn speed(&self, base_speed: f32) -> f32 {
+        match self {
+            GhostMode::Chase | GhostMode::Scatter => base_speed,
+            GhostMode::Frightened => FRIGHTENED_SPEED,
+            GhostMode::Eaten => EATEN_SPEED,
         }
     }
 }
 
-#[derive(Resource)]
+/// How long each scatter and chase period lasts on [level], in seconds, starting with scatter.
+/// The chase after the last one goes on for the rest of the level.
+fn mode_schedule(level: u32) -> [f32; 7] {
+    match level {
+        0 | 1 => [7., 20., 7., 20., 5., 20., 5.],
+        2..=4 => [7., 20., 7., 20., 5., 1033., 1. / 60.],
+        _ => [5., 20., 5., 20., 5., 1037., 1. / 60.],
+    }
+}
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
 pub struct GhostModeRes {
     pub global_mode: GhostMode,
+    /// The scatter periods started this level.
+    pub scatters: u32,
+    /// The period of the level's schedule being played, see [mode_schedule].
+    period: usize,
+    /// How long the period has left, none once the ghosts chase for the rest of the level.
+    timer: Option<Timer>,
 }
 
 impl Default for GhostModeRes {
     fn default() -> Self {
-        Self {
+        Self::new(1)
+    }
+}
+
+impl GhostModeRes {
+    /// Starts [level] with the first scatter period, like a new life does.
+    pub fn new(level: u32) -> Self {
+        let mut mode_res = Self {
             global_mode: GhostMode::Chase,
+            scatters: 0,
+            period: 0,
+            timer: None,
+        };
+        mode_res.start_period(level, 0);
+        mode_res
+    }
+
+    /// Switches the ghosts to [mode] for the rest of the level, stopping the schedule.
+    pub fn set_global_mode(&mut self, mode: GhostMode) {
+        self.timer = None;
+        self.switch_to(mode);
+    }
+
+    pub fn in_first_scatter(&self) -> bool {
+        self.global_mode == GhostMode::Scatter && self.scatters <= 1
+    }
+
+    fn switch_to(&mut self, mode: GhostMode) {
+        if mode == GhostMode::Scatter {
+            self.scatters += 1;
+        }
+        self.global_mode = mode;
+    }
+
+    fn start_period(&mut self, level: u32, period: usize) {
+        self.period = period;
+        self.timer = mode_schedule(level)
+            .get(period)
+            .map(|&seconds| Timer::from_seconds(seconds, TimerMode::Once));
+        self.switch_to(if period.is_multiple_of(2) {
+            GhostMode::Scatter
+        } else {
+            GhostMode::Chase
+        });
+    }
+}
+
+/// Keeps track of how long the ghosts remain frightened.
+#[derive(Resource, Default)]
+pub struct FrightenedRes {
+    pub timer: Option<Timer>,
+}
+
+impl FrightenedRes {
+    /// Whether frightened ghosts should be flashing, warning that they are about to recover.
+    pub fn is_flashing(&self) -> bool {
+        self.timer
+            .as_ref()
+            .is_some_and(|t| t.remaining_secs() <= FRIGHTENED_FLASH_SECONDS)
+    }
+}
+
+/// How long the ghosts stay frightened on [level], in seconds.
+fn frightened_seconds(level: u32) -> f32 {
+    match level {
+        0 | 1 => 6.,
+        2 | 6 | 10 => 5.,
+        3 => 4.,
+        4 | 14 => 3.,
+        5 | 7 | 8 | 11 => 2.,
+        9 | 12 | 13 | 15 | 16 | 18 => 1.,
+        _ => 0.,
+    }
+}
+
+pub fn start_frightened(
+    mut eaten_events: EventReader<ScorableEatenEvent>,
+    mut frightened: ResMut<FrightenedRes>,
+    mut score: ResMut<Score>,
+    level: Res<Level>,
+    ghosts: Query<(&mut Ghost, &mut GhostTarget, &mut Movable)>,
+) {
+    if eaten_events
+        .read()
+        .filter(|e| e.scorable == Scorable::Energizer)
+        .count()
+        == 0
+    {
+        return;
+    }
+
+    score.ghosts_eaten = 0;
+
+    let seconds = frightened_seconds(**level);
+    if seconds <= 0. {
+        // The ghosts no longer get frightened on higher levels, but still reverse.
+        for (_, mut target, _) in ghosts {
+            target.should_reverse = true;
+        }
+        return;
+    }
+
+    frightened.timer = Some(Timer::from_seconds(seconds, TimerMode::Once));
+
+    for (mut ghost, mut target, mut movable) in ghosts {
+        if ghost.current_mode == GhostMode::Eaten {
+            continue;
+        }
+
+        if ghost.current_mode != GhostMode::Frightened {
+            target.should_reverse = true;
+        }
+        ghost.current_mode = GhostMode::Frightened;
+        movable.speed = ghost.current_mode.speed(ghost.base_speed);
+    }
+}
+
+pub fn update_frightened(
+    time: Res<Time>,
+    mut frightened: ResMut<FrightenedRes>,
+    mode_res: Res<GhostModeRes>,
+    ghosts: Query<(&mut Ghost, &mut Movable)>,
+) {
+    let Some(timer) = frightened.timer.as_mut() else {
+        return;
+    };
+
+    timer.tick(time.delta());
+    if !timer.finished() {
+        return;
+    }
+
+    frightened.timer = None;
+
+    for (mut ghost, mut movable) in ghosts {
+        if ghost.current_mode == GhostMode::Frightened {
+            ghost.current_mode = mode_res.global_mode.clone();
+            movable.speed = ghost.current_mode.speed(ghost.base_speed);
+        }
+    }
+}
+
+/// Alternates the ghosts between scatter and chase following the level's schedule, which is put
+/// on hold while they're frightened.
+pub fn update_ghost_mode(
+    time: Res<Time>,
+    level: Res<Level>,
+    frightened: Res<FrightenedRes>,
+    mut mode_res: ResMut<GhostModeRes>,
+) {
+    if frightened.timer.is_some() {
+        return;
+    }
+
+    let Some(timer) = mode_res.timer.as_mut() else {
+        return;
+    };
+
+    if timer.tick(time.delta()).finished() {
+        let next = mode_res.period + 1;
+        mode_res.start_period(**level, next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_start_with_the_first_scatter() {
+        for level in [1, 3, 7] {
+            let mode_res = GhostModeRes::new(level);
+            assert!(mode_res.in_first_scatter());
+            assert_eq!(
+                mode_res.timer.as_ref().map(|t| t.duration().as_secs_f32()),
+                Some(mode_schedule(level)[0])
+            );
         }
     }
+
+    #[test]
+    fn the_ghosts_chase_for_good_after_the_last_scatter() {
+        let mut mode_res = GhostModeRes::new(1);
+        for period in 1..=7 {
+            mode_res.start_period(1, period);
+            assert!(!mode_res.in_first_scatter());
+        }
+
+        assert_eq!(mode_res.global_mode, GhostMode::Chase);
+        assert_eq!(mode_res.scatters, 4);
+        assert!(mode_res.timer.is_none());
+    }
 }
@@ -4,20 +4,38 @@ use bevy::prelude::*;
 
 use crate::{
     common::{Direction, TilePos},
-    components::{Ghost, GhostTarget, Movable, Position},
+    components::{Ghost, GhostController, GhostTarget, Movable, Position, QueableDirection},
     ghosts::ghost_mode::{GhostMode, GhostModeRes},
+    input::ActionState,
     map::MAP,
+    player::steer,
+    players::Players,
+    rng::GameRng,
+    rules::GameRules,
 };
 
-pub fn ghost_movement(ghosts: Query<(&Position, &mut Movable, &mut GhostTarget)>) {
-    for (position, mut movable, mut target) in ghosts {
+/// Where the eyes of an eaten ghost return to before reviving.
+pub const GHOST_HOUSE_ENTRANCE: TilePos = TilePos { x: 13, y: 11 };
+
+pub fn ghost_movement(
+    mut rng: ResMut<GameRng>,
+    ghosts: Query<(
+        &Ghost,
+        &GhostController,
+        &Position,
+        &mut Movable,
+        &mut GhostTarget,
+        &mut QueableDirection,
+    )>,
+) {
+    for (ghost, controller, position, mut movable, mut target, mut queued_dir) in ghosts {
         let tile_pos: TilePos = (&position.0).into();
 
         let has_reached_destination =
             tile_pos == movable.target_tile && position.in_middle_of_tile();
 
         if !has_reached_destination {
-            return;
+            continue;
         }
 
         // Check if we should reverse.
@@ -26,13 +44,22 @@ pub fn ghost_movement(ghosts: Query<(&Position, &mut Movable, &mut GhostTarget)>
             movable.direction = movable.direction.opposite();
         }
 
-        let Some(target_tile) = target.tile.as_ref() else {
-            return;
+        let steered = matches!(controller, GhostController::Player(_))
+            && ghost.current_mode != GhostMode::Eaten;
+
+        let next = if steered {
+            steered_next_tile(&tile_pos, &movable.direction, &mut queued_dir)
+        } else if ghost.current_mode == GhostMode::Frightened || target.wander {
+            random_next_tile(&tile_pos, &movable.direction, &mut rng)
+        } else {
+            let Some(target_tile) = target.tile.as_ref() else {
+                continue;
+            };
+            next_tile(&tile_pos, &movable.direction, target_tile)
         };
 
-        let Some((new_dest, new_dir)) = next_tile(&tile_pos, &movable.direction, target_tile)
-        else {
-            return;
+        let Some((new_dest, new_dir)) = next else {
+            continue;
         };
 
         movable.target_tile = new_dest;
@@ -40,17 +67,24 @@ pub fn ghost_movement(ghosts: Query<(&Position, &mut Movable, &mut GhostTarget)>
     }
 }
 
+/// The tiles a ghost may move to next, ghosts never reverse on their own and can't turn up in
+/// the up block areas.
+fn allowed_neighbours(current_pos: &TilePos, current_dir: &Direction) -> Vec<(TilePos, Direction)> {
+    MAP.get_empty_neighbours(current_pos)
+        .into_iter()
+        .filter(|(_, dir)| &dir.opposite() != current_dir)
+        .filter(|(tile, _)| !MAP.is_wall(tile))
+        .filter(|(_, dir)| !(dir == &Direction::Up && MAP.is_in_ghost_up_block_area(current_pos)))
+        .collect()
+}
+
 pub fn next_tile(
     current_pos: &TilePos,
     current_dir: &Direction,
     target_pos: &TilePos,
 ) -> Option<(TilePos, Direction)> {
-    let mut neighbours = MAP
-        .get_empty_neighbours(&current_pos)
+    let mut neighbours = allowed_neighbours(current_pos, current_dir)
         .into_iter()
-        .filter(|(_, dir)| &dir.opposite() != current_dir)
-        .filter(|(tile, _)| !MAP.is_wall(tile))
-        .filter(|(_, dir)| !(dir == &Direction::Up && MAP.is_in_ghost_up_block_area(&current_pos)))
         .map(|(pos, dir)| (pos.dist_to(target_pos), pos, dir))
         .collect::<Vec<_>>();
 
@@ -74,18 +108,187 @@ pub fn next_tile(
     Some((new_dest, new_dir))
 }
 
+/// The order the arcade tries directions in when the random one isn't allowed.
+const RANDOM_TURN_ORDER: [Direction; 4] = [
+    Direction::Up,
+    Direction::Left,
+    Direction::Down,
+    Direction::Right,
+];
+
+/// Frightened ghosts pick a random direction at every tile, like the arcade they try the
+/// following directions in [RANDOM_TURN_ORDER] until one is allowed.
+fn random_next_tile(
+    current_pos: &TilePos,
+    current_dir: &Direction,
+    rng: &mut GameRng,
+) -> Option<(TilePos, Direction)> {
+    let mut neighbours = allowed_neighbours(current_pos, current_dir);
+    let start = rng.below(RANDOM_TURN_ORDER.len() as u32) as usize;
+
+    RANDOM_TURN_ORDER
+        .iter()
+        .cycle()
+        .skip(start)
+        .take(RANDOM_TURN_ORDER.len())
+        .find_map(|dir| {
+            let index = neighbours.iter().position(|(_, d)| d == dir)?;
+            Some(neighbours.swap_remove(index))
+        })
+}
+
+/// A ghost steered by a player takes the queued direction when it's allowed from here, otherwise
+/// it keeps going straight, or takes the first way out as ghosts can't stop.
+fn steered_next_tile(
+    current_pos: &TilePos,
+    current_dir: &Direction,
+    queued_dir: &mut QueableDirection,
+) -> Option<(TilePos, Direction)> {
+    let mut neighbours = allowed_neighbours(current_pos, current_dir);
+
+    if let Some(next_dir) = queued_dir.next_direction.clone() {
+        if let Some(index) = neighbours.iter().position(|(_, dir)| dir == &next_dir) {
+            queued_dir.clear();
+            return Some(neighbours.swap_remove(index));
+        }
+        queued_dir.tile_passed();
+    }
+
+    match neighbours.iter().position(|(_, dir)| dir == current_dir) {
+        Some(index) => Some(neighbours.swap_remove(index)),
+        None => neighbours.into_iter().next(),
+    }
+}
+
+/// Reads the steering of the players controlling a ghost in versus mode.
+pub fn control_ghosts(
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    rules: Res<GameRules>,
+    ghosts: Query<(&GhostController, &Movable, &mut QueableDirection)>,
+) {
+    for (controller, movable, mut queued_dir) in ghosts {
+        let GhostController::Player(player) = controller else {
+            continue;
+        };
+
+        queued_dir.tick(time.delta());
+        steer(&actions, &rules, *player, movable, &mut queued_dir);
+    }
+}
+
+/// Hands the ghost picked by [GameRules::versus_ghost] to the second player in versus mode, the
+/// AI steers all of them otherwise.
+pub fn assign_ghost_controllers(
+    players: Res<Players>,
+    rules: Res<GameRules>,
+    ghosts: Query<(&Ghost, &mut GhostController)>,
+) {
+    for (ghost, mut controller) in ghosts {
+        *controller = if players.versus && ghost.ghost == rules.versus_ghost {
+            GhostController::Player(1)
+        } else {
+            GhostController::Ai
+        };
+    }
+}
+
 pub fn ghost_handle_scatter(
     ghosts: Query<(&mut Ghost, &mut GhostTarget)>,
     mode_res: Res<GhostModeRes>,
+    rules: Res<GameRules>,
 ) {
     for (mut ghost, mut target) in ghosts {
+        if matches!(ghost.current_mode, GhostMode::Frightened | GhostMode::Eaten) {
+            target.wander = false;
+            continue;
+        }
+
         if mode_res.global_mode != ghost.current_mode {
             target.should_reverse = true;
             ghost.current_mode = mode_res.global_mode.clone();
         }
 
+        target.wander =
+            mode_res.in_first_scatter() && rules.profile.wanders_in_first_scatter(&ghost.ghost);
+
         if ghost.current_mode == GhostMode::Scatter {
             target.tile = Some(ghost.corner_tile.clone());
         }
     }
 }
+
+/// Sends the eyes of eaten ghosts back home, where they revive.
+pub fn ghost_handle_eaten(
+    ghosts: Query<(&mut Ghost, &Position, &mut Movable, &mut GhostTarget)>,
+    mode_res: Res<GhostModeRes>,
+) {
+    for (mut ghost, position, mut movable, mut target) in ghosts {
+        if ghost.current_mode != GhostMode::Eaten {
+            continue;
+        }
+
+        if TilePos::from(&position.0) == GHOST_HOUSE_ENTRANCE && position.in_middle_of_tile() {
+            ghost.current_mode = mode_res.global_mode.clone();
+            movable.speed = ghost.current_mode.speed(ghost.base_speed);
+        } else {
+            target.tile = Some(GHOST_HOUSE_ENTRANCE);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::{common::PixelPos, ghosts::GhostName};
+
+    /// A ghost heading right to [target_tile], chasing the top left of the maze.
+    fn spawn_ghost(
+        world: &mut World,
+        name: GhostName,
+        position: PixelPos,
+        target_tile: TilePos,
+    ) -> Entity {
+        world
+            .spawn((
+                Ghost::new(name, TilePos { x: 0, y: 0 }, 0.75),
+                GhostController::Ai,
+                Position(position),
+                Movable::new(target_tile, Direction::Right, 0.75),
+                GhostTarget {
+                    tile: Some(TilePos { x: 1, y: 1 }),
+                    ..default()
+                },
+                QueableDirection::default(),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn a_ghost_between_tiles_does_not_hold_up_the_others() {
+        let mut world = World::new();
+        world.insert_resource(GameRng::from_seed(1));
+
+        // Spawned first, so it's the first ghost looked at.
+        let moving = spawn_ghost(
+            &mut world,
+            GhostName::Blinky,
+            PixelPos { x: 48, y: 12 },
+            TilePos { x: 7, y: 1 },
+        );
+        let arrived = spawn_ghost(
+            &mut world,
+            GhostName::Pinky,
+            PixelPos::from(&TilePos { x: 6, y: 1 }),
+            TilePos { x: 6, y: 1 },
+        );
+
+        world.run_system_once(ghost_movement).unwrap();
+
+        let target_of = |entity| world.get::<Movable>(entity).unwrap().target_tile.clone();
+        assert_eq!(target_of(moving), TilePos { x: 7, y: 1 });
+        assert_ne!(target_of(arrived), TilePos { x: 6, y: 1 });
+    }
+}
@@ -1,144 +1,88 @@
-use bevy::{prelude::*, sprite::Anchor};
+use bevy::prelude::*;
 
 use crate::{
-    common::{Character, Direction, PixelPos, TilePos},
-    components::{AnimationIndices, AnimationTimer, Ghost, GhostTarget, Movable, Player, Position},
-    debug::DebugRes,
-    ghosts::{GhostName, blinky::Blinky, ghost_mode::GhostMode},
-    map::TILE_SIZE,
+    common::{Direction, TilePos},
+    ghosts::{
+        GhostName,
+        brain::{GhostBrain, GhostView, PacmanView, WorldSnapshot},
+    },
+    rules::TargetingAccuracy,
 };
 
-#[derive(Component)]
-pub struct Inky {
-    intermediate_tile: Option<TilePos>,
-}
-
-pub fn inky_bundle(
-    texture: Handle<Image>,
-    texture_atlas_layout: Handle<TextureAtlasLayout>,
-) -> impl Bundle {
-    let mut inky_indices =
-        AnimationIndices::new(vec![84, 85], vec![86, 87], vec![88, 89], vec![90, 91]);
-
-    // let start_tile_pos = TilePos { x: 11, y: 14 };
-    let start_tile_pos = TilePos { x: 13, y: 11 };
-    let mut start_pos: PixelPos = (&start_tile_pos).into();
-    start_pos.x += TILE_SIZE / 2;
-
-    let visual_start_pos = start_pos.to_character_display_pos();
-
-    let mut sprite = Sprite::from_atlas_image(
-        texture,
-        TextureAtlas {
-            layout: texture_atlas_layout,
-            index: inky_indices.next(&Direction::Up),
-        },
-    );
-
-    sprite.anchor = Anchor::TopLeft;
-
-    (
-        sprite,
-        Inky {
-            intermediate_tile: None,
-        },
-        Character::Ghost {
-            name: GhostName::Inky,
-        },
-        GhostTarget::default(),
-        Ghost::new(GhostName::Inky, TilePos { x: 27, y: 31 }),
-        Transform::from_translation(visual_start_pos),
-        inky_indices,
-        AnimationTimer(Timer::from_seconds(0.08, TimerMode::Repeating)),
-        Position(start_pos.clone()),
-        Movable::new(start_tile_pos, Direction::Left, 0.75),
-    )
-}
-
-pub fn inky_update_target(
-    inky: Single<(&mut GhostTarget, &mut Inky, &Ghost)>,
-    blinky: Single<&Position, With<Blinky>>,
-    pacman_pos: Single<(&Position, &Movable), With<Player>>,
-) {
-    let (pos, movable) = pacman_pos.into_inner();
-    let pacman_pos: TilePos = (&pos.0).into();
-
-    let (mut ghost_target, mut inky, ghost) = inky.into_inner();
-
-    if ghost.current_mode != GhostMode::Chase {
-        return;
+/// Inky heads for the tile as far past the tile two tiles ahead of Pac-Man as Blinky is from it.
+pub struct InkyBrain;
+
+impl InkyBrain {
+    /// The chased Pac-Man, the tile two tiles ahead of him and Blinky's tile, None without
+    /// Pac-Man or Blinky.
+    fn pivot_tiles<'a>(
+        &self,
+        me: &GhostView,
+        world: &'a WorldSnapshot,
+    ) -> Option<(&'a PacmanView, TilePos, TilePos)> {
+        let pacman = world.chased_pacman(me)?;
+        let blinky = world.ghost(&GhostName::Blinky)?;
+
+        let intermediate_tile =
+            world
+                .rules
+                .targeting
+                .tiles_ahead(&pacman.tile, &pacman.direction, 2);
+
+        Some((pacman, intermediate_tile, blinky.tile.clone()))
     }
 
-    let intermediate_pos: TilePos = match movable.direction {
-        Direction::Up => TilePos {
-            x: pacman_pos.x - 2,
-            y: pacman_pos.y - 2,
-        },
-        Direction::Right => TilePos {
-            x: pacman_pos.x + 2,
-            y: pacman_pos.y,
-        },
-        Direction::Down => TilePos {
-            x: pacman_pos.x,
-            y: pacman_pos.y + 2,
-        },
-        Direction::Left => TilePos {
-            x: pacman_pos.x - 2,
-            y: pacman_pos.y,
-        },
-    };
-    let blinky_pos: TilePos = (&blinky.0).into();
-
-    let delta = intermediate_pos.clone() - blinky_pos;
-
-    let inky_target = intermediate_pos.clone() + delta;
-
-    inky.intermediate_tile = Some(intermediate_pos);
-    ghost_target.tile = Some(inky_target);
-}
-
-pub fn inky_debug(
-    inky: Single<(&Inky, &GhostTarget, &Ghost)>,
-    blinky: Single<&Position, With<Blinky>>,
-    debug_mode: Res<DebugRes>,
-    mut gizmos: Gizmos,
-) {
-    if !debug_mode.debug_mode {
-        return;
+    /// Mirrors Blinky's tile around the intermediate tile.
+    fn target(intermediate_tile: &TilePos, blinky_tile: &TilePos) -> TilePos {
+        let delta = intermediate_tile.clone() - blinky_tile.clone();
+        intermediate_tile.clone() + delta
     }
+}
 
-    let (inky, target, ghost) = inky.into_inner();
+impl GhostBrain for InkyBrain {
+    fn chase_target(&self, me: &GhostView, world: &WorldSnapshot) -> Option<TilePos> {
+        let (_, intermediate_tile, blinky_tile) = self.pivot_tiles(me, world)?;
 
-    if ghost.current_mode != GhostMode::Chase {
-        return;
+        Some(Self::target(&intermediate_tile, &blinky_tile))
     }
 
-    let Some(target) = target.tile.as_ref() else {
-        return;
-    };
-
-    let color = GhostName::Inky.get_color();
-
-    let Some(intermediate_tile) = inky.intermediate_tile.as_ref() else {
-        return;
-    };
-
-    let intermediate_tile_pos = intermediate_tile.to_center_display_pos();
-
-    gizmos.rect_2d(
-        Isometry2d::from_translation(Vec2::new(
-            intermediate_tile_pos.x as f32,
-            intermediate_tile_pos.y as f32,
-        )),
-        Vec2::splat(6.),
-        color,
-    );
-
-    let blinky_pos: TilePos = (&blinky.0).into();
-
-    gizmos.line_2d(
-        blinky_pos.to_center_display_pos(),
-        target.to_center_display_pos(),
-        color,
-    );
+    fn draw_debug(&self, me: &GhostView, world: &WorldSnapshot, gizmos: &mut Gizmos) {
+        let Some((pacman, intermediate_tile, blinky_tile)) = self.pivot_tiles(me, world) else {
+            return;
+        };
+        let target = Self::target(&intermediate_tile, &blinky_tile);
+
+        let color = GhostName::Inky.get_color();
+
+        let intermediate_tile_pos = intermediate_tile.to_center_display_pos();
+        let intermediate_isometry = Isometry2d::from_translation(intermediate_tile_pos);
+
+        // Arcade targeting is marked with a square, corrected targeting with a circle.
+        match world.rules.targeting {
+            TargetingAccuracy::Arcade => {
+                gizmos.rect_2d(intermediate_isometry, Vec2::splat(6.), color);
+            }
+            TargetingAccuracy::Corrected => {
+                gizmos.circle_2d(intermediate_isometry, 3., color);
+            }
+        }
+
+        if world.rules.targeting == TargetingAccuracy::Arcade && pacman.direction == Direction::Up {
+            // Show how far the overflow bug pushed the intermediate tile from straight ahead.
+            let straight_ahead =
+                TargetingAccuracy::Corrected.tiles_ahead(&pacman.tile, &Direction::Up, 2);
+
+            gizmos.line_2d(
+                straight_ahead.to_center_display_pos(),
+                intermediate_tile_pos,
+                Color::WHITE,
+            );
+        }
+
+        gizmos.line_2d(
+            blinky_tile.to_center_display_pos(),
+            target.to_center_display_pos(),
+            color,
+        );
+    }
 }
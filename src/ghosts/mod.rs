@@ -1,8 +1,22 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
-use bevy::prelude::*;
+use bevy::{prelude::*, sprite::Anchor};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    animation::{AnimationLibrary, direction_clip},
+    common::{Character, Direction, PixelPos, TilePos},
+    components::{
+        Ghost, GhostController, GhostTarget, Movable, Position, QueableDirection, SpawnPoint,
+    },
+    ghosts::{
+        blinky::BlinkyBrain, brain::Brain, clyde::ClydeBrain, inky::InkyBrain, pinky::PinkyBrain,
+    },
+    map::TILE_SIZE,
+};
 
 pub mod blinky;
+pub mod brain;
 pub mod clyde;
 pub mod ghost_debug;
 pub mod ghost_mode;
@@ -10,7 +24,10 @@ pub mod ghost_movement;
 pub mod inky;
 pub mod pinky;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// How fast ghosts move when neither frightened nor eaten.
+const GHOST_SPEED: f32 = 0.75;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GhostName {
     Blinky,
     Inky,
@@ -19,7 +36,7 @@ pub enum GhostName {
 }
 
 impl GhostName {
-    fn get_color(&self) -> Color {
+    pub fn get_color(&self) -> Color {
         match self {
             GhostName::Blinky => Color::linear_rgb(1.0, 0., 0.),
             GhostName::Inky => Color::linear_rgb(0., 0.8, 0.8),
@@ -34,3 +51,116 @@ impl Display for GhostName {
         write!(f, "{:?}", self)
     }
 }
+
+impl FromStr for GhostName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "blinky" => Ok(GhostName::Blinky),
+            "inky" => Ok(GhostName::Inky),
+            "pinky" => Ok(GhostName::Pinky),
+            "clyde" => Ok(GhostName::Clyde),
+            _ => Err(format!("Unknown ghost '{s}'")),
+        }
+    }
+}
+
+impl GhostName {
+    /// The player this ghost chases with [crate::rules::CoopTargeting::Split].
+    fn assigned_player(&self) -> usize {
+        match self {
+            GhostName::Blinky | GhostName::Inky => 0,
+            GhostName::Pinky | GhostName::Clyde => 1,
+        }
+    }
+}
+
+/// What sets a ghost apart, adding a ghost only takes a line in [GhostName::spec].
+pub struct GhostSpec {
+    pub brain: Brain,
+    /// The tile the ghost starts on, it's placed between this tile and the one to its right.
+    pub spawn: TilePos,
+    /// The corner the ghost heads for when scattering.
+    pub scatter_tile: TilePos,
+}
+
+impl GhostName {
+    pub fn spec(&self) -> GhostSpec {
+        let (brain, spawn, scatter_tile) = match self {
+            GhostName::Blinky => (Brain::new(BlinkyBrain), (13, 11), (25, -4)),
+            GhostName::Pinky => (Brain::new(PinkyBrain), (13, 11), (2, -4)),
+            GhostName::Inky => (Brain::new(InkyBrain), (13, 11), (27, 31)),
+            GhostName::Clyde => (Brain::new(ClydeBrain::new(8.)), (13, 11), (0, 31)),
+        };
+
+        GhostSpec {
+            brain,
+            spawn: TilePos {
+                x: spawn.0,
+                y: spawn.1,
+            },
+            scatter_tile: TilePos {
+                x: scatter_tile.0,
+                y: scatter_tile.1,
+            },
+        }
+    }
+
+    /// The name of the ghost's clips in the [AnimationLibrary].
+    pub fn animation_name(&self) -> &'static str {
+        match self {
+            GhostName::Blinky => "blinky",
+            GhostName::Inky => "inky",
+            GhostName::Pinky => "pinky",
+            GhostName::Clyde => "clyde",
+        }
+    }
+}
+
+/// A ghost with the brain, spawn and scatter corner of its [GhostName::spec].
+pub fn ghost_bundle(
+    name: GhostName,
+    texture: Handle<Image>,
+    texture_atlas_layout: Handle<TextureAtlasLayout>,
+    animations: &AnimationLibrary,
+) -> impl Bundle {
+    let GhostSpec {
+        brain,
+        spawn,
+        scatter_tile,
+    } = name.spec();
+    let direction = Direction::Left;
+
+    let animation = animations.animation(name.animation_name(), direction_clip(&direction));
+
+    let mut start_pos: PixelPos = (&spawn).into();
+    start_pos.x += TILE_SIZE / 2;
+
+    let mut sprite = Sprite::from_atlas_image(
+        texture,
+        TextureAtlas {
+            layout: texture_atlas_layout,
+            index: animation.index(),
+        },
+    );
+    sprite.anchor = Anchor::TopLeft;
+
+    let position = Position(start_pos.clone());
+    let movable = Movable::new(spawn, direction, GHOST_SPEED);
+
+    (
+        sprite,
+        Character::Ghost { name: name.clone() },
+        GhostTarget::default(),
+        GhostController::default(),
+        brain,
+        QueableDirection::default(),
+        Ghost::new(name, scatter_tile, GHOST_SPEED),
+        Transform::from_translation(start_pos.to_character_display_pos()),
+        animation,
+        SpawnPoint::new(&position, &movable),
+        position,
+        movable,
+    )
+}
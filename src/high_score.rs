@@ -0,0 +1,351 @@
+use std::{
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{log, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::load_ron_or,
+    events::GameOverEvent,
+    game_state::GameState,
+    input::{Action, ActionState},
+    level::Level,
+    players::Players,
+    score::Score,
+    theme::Theme,
+};
+
+pub const HIGH_SCORES_PATH: &str = "highscores.ron";
+pub const MAX_HIGH_SCORES: usize = 10;
+const INITIALS_LENGTH: usize = 3;
+const INITIALS_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ.- ";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighScoreEntry {
+    pub initials: String,
+    pub score: u32,
+    pub level: u32,
+    /// The date the score was set, as YYYY-MM-DD.
+    pub date: String,
+}
+
+/// The top scores, sorted from highest to lowest.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    pub entries: Vec<HighScoreEntry>,
+}
+
+impl HighScores {
+    /// Loads the high scores from [path], starting with an empty table if there are none yet.
+    pub fn load_or_default(path: &str) -> Self {
+        load_ron_or(path, "high scores", Self::default)
+    }
+
+    pub fn save(&self, path: &str) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(err) => {
+                log::error!("Failed to serialize high scores ({err})");
+                return;
+            }
+        };
+
+        if let Err(err) = fs::write(path, contents) {
+            log::error!("Failed to save high scores to {path} ({err})");
+        }
+    }
+
+    pub fn top_score(&self) -> u32 {
+        self.entries.first().map(|e| e.score).unwrap_or_default()
+    }
+
+    /// Returns whether [score] would make it onto the table.
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_HIGH_SCORES
+                || self.entries.iter().any(|e| score > e.score))
+    }
+
+    pub fn insert(&mut self, entry: HighScoreEntry) {
+        let index = self
+            .entries
+            .iter()
+            .position(|e| entry.score > e.score)
+            .unwrap_or(self.entries.len());
+
+        self.entries.insert(index, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+    }
+
+    fn table_text(&self) -> String {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(i, e)| {
+                format!(
+                    "{:>2}. {} {:>8} L{:<3} {}",
+                    i + 1,
+                    e.initials,
+                    e.score,
+                    e.level,
+                    e.date
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Today's date (UTC) as YYYY-MM-DD.
+fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default() as i64;
+
+    date_from_timestamp(secs)
+}
+
+/// The date (UTC) [secs] seconds after 1970-01-01 00:00 as YYYY-MM-DD.
+fn date_from_timestamp(secs: i64) -> String {
+    date_from_days(secs.div_euclid(86_400))
+}
+
+/// The date [days] after 1970-01-01 as YYYY-MM-DD.
+fn date_from_days(days: i64) -> String {
+    // Converts days since 1970-01-01 to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[derive(Resource)]
+pub struct InitialsEntry {
+    /// Index into [INITIALS_ALPHABET] for every letter.
+    letters: [usize; INITIALS_LENGTH],
+    cursor: usize,
+    /// The score (and level) the initials are entered for.
+    score: u32,
+    level: u32,
+}
+
+impl InitialsEntry {
+    fn initials(&self) -> String {
+        self.letters
+            .iter()
+            .map(|&i| INITIALS_ALPHABET[i] as char)
+            .collect()
+    }
+
+    fn display_text(&self) -> String {
+        let letters = self.initials();
+        let cursor = (0..INITIALS_LENGTH)
+            .map(|i| if i == self.cursor { '^' } else { ' ' })
+            .collect::<String>();
+
+        format!("{letters}\n{cursor}")
+    }
+}
+
+#[derive(Component)]
+pub struct InitialsText;
+
+pub fn handle_game_over(
+    mut game_over_events: EventReader<GameOverEvent>,
+    score: Res<Score>,
+    level: Res<Level>,
+    players: Res<Players>,
+    high_scores: Res<HighScores>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if game_over_events.read().count() == 0 {
+        return;
+    }
+
+    // With two players, the best of them gets to enter their initials.
+    let (best_score, _) = players.best_result(&score, &level);
+    if high_scores.qualifies(best_score) {
+        next_state.set(GameState::EnterInitials);
+    } else {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+pub fn setup_initials_entry(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+    score: Res<Score>,
+    level: Res<Level>,
+    players: Res<Players>,
+) {
+    let (score, level) = players.best_result(&score, &level);
+    let entry = InitialsEntry {
+        letters: [0; INITIALS_LENGTH],
+        cursor: 0,
+        score,
+        level,
+    };
+
+    let text_font = TextFont {
+        font: asset_server.load(&theme.font),
+        font_size: 48.0,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            StateScoped(GameState::EnterInitials),
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(40.),
+                ..default()
+            },
+            BackgroundColor(theme.colours.background()),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("NEW HIGH SCORE!\nENTER YOUR INITIALS"),
+                text_font.clone().with_font_size(28.),
+                TextColor(theme.colours.highlight()),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+            parent.spawn((
+                Text::new(entry.display_text()),
+                InitialsText,
+                text_font.clone().with_font_size(96.),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+            parent.spawn((
+                Text::new("UP/DOWN TO CHANGE LETTER\nLEFT/RIGHT TO MOVE, CONFIRM WHEN DONE"),
+                text_font.clone().with_font_size(14.),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+        });
+
+    commands.insert_resource(entry);
+}
+
+pub fn update_initials_entry(
+    actions: Res<ActionState>,
+    mut entry: ResMut<InitialsEntry>,
+    mut high_scores: ResMut<HighScores>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut initials_text: Single<&mut Text, With<InitialsText>>,
+) {
+    let cursor = entry.cursor;
+    let alphabet_len = INITIALS_ALPHABET.len();
+
+    if actions.just_pressed(Action::MoveUp) {
+        entry.letters[cursor] = (entry.letters[cursor] + 1) % alphabet_len;
+    } else if actions.just_pressed(Action::MoveDown) {
+        entry.letters[cursor] = (entry.letters[cursor] + alphabet_len - 1) % alphabet_len;
+    } else if actions.just_pressed(Action::MoveLeft) {
+        entry.cursor = cursor.saturating_sub(1);
+    } else if actions.just_pressed(Action::MoveRight) {
+        entry.cursor = (cursor + 1).min(INITIALS_LENGTH - 1);
+    } else if actions.just_pressed(Action::Confirm) {
+        if cursor + 1 < INITIALS_LENGTH {
+            entry.cursor += 1;
+        } else {
+            high_scores.insert(HighScoreEntry {
+                initials: entry.initials(),
+                score: entry.score,
+                level: entry.level,
+                date: today(),
+            });
+            high_scores.save(HIGH_SCORES_PATH);
+            next_state.set(GameState::GameOver);
+        }
+    }
+
+    initials_text.0 = entry.display_text();
+}
+
+pub fn setup_game_over_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    high_scores: Res<HighScores>,
+    theme: Res<Theme>,
+) {
+    let text_font = TextFont {
+        font: asset_server.load(&theme.font),
+        font_size: 48.0,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            StateScoped(GameState::GameOver),
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(40.),
+                ..default()
+            },
+            BackgroundColor(theme.colours.background()),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("GAME OVER"),
+                text_font.clone(),
+                TextColor(theme.colours.alert()),
+            ));
+            parent.spawn((
+                Text::new("HIGH SCORES"),
+                text_font.clone().with_font_size(28.),
+            ));
+            parent.spawn((
+                Text::new(high_scores.table_text()),
+                text_font.clone().with_font_size(16.),
+            ));
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_from_days_since_the_epoch() {
+        assert_eq!(date_from_days(0), "1970-01-01");
+        assert_eq!(date_from_days(-1), "1969-12-31");
+        assert_eq!(date_from_days(59), "1970-03-01");
+        assert_eq!(date_from_days(10_957), "2000-01-01");
+        assert_eq!(date_from_days(11_016), "2000-02-29");
+        assert_eq!(date_from_days(11_017), "2000-03-01");
+        assert_eq!(date_from_days(47_540), "2100-02-28");
+        assert_eq!(date_from_days(47_541), "2100-03-01");
+    }
+
+    #[test]
+    fn dates_from_timestamps() {
+        assert_eq!(date_from_timestamp(0), "1970-01-01");
+        assert_eq!(date_from_timestamp(-1), "1969-12-31");
+        assert_eq!(date_from_timestamp(951_782_400), "2000-02-29");
+        assert_eq!(date_from_timestamp(1_677_628_799), "2023-02-28");
+        assert_eq!(date_from_timestamp(1_677_628_800), "2023-03-01");
+        assert_eq!(date_from_timestamp(1_704_067_199), "2023-12-31");
+        assert_eq!(date_from_timestamp(1_704_067_200), "2024-01-01");
+        assert_eq!(date_from_timestamp(1_709_164_800), "2024-02-29");
+        assert_eq!(date_from_timestamp(1_709_251_199), "2024-02-29");
+    }
+}
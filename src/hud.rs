@@ -0,0 +1,226 @@
+use bevy::{prelude::*, sprite::Anchor, text::FontSmoothing};
+
+use crate::{
+    common::{CharacterSprites, TilePos},
+    fruit::Fruit,
+    high_score::HighScores,
+    level::Level,
+    lives::Lives,
+    map::{MAP_WIDTH, TILE_SIZE},
+    players::Players,
+    score::Score,
+    theme::Theme,
+};
+
+/// The amount of tile rows above the maze used by the HUD.
+pub const HUD_ROWS_ABOVE: i32 = 3;
+/// The amount of tile rows below the maze used by the HUD.
+pub const HUD_ROWS_BELOW: i32 = 2;
+
+const HUD_Z: f32 = 5.;
+const UP_BLINK_SECONDS: f32 = 0.25;
+const LIFE_ICON_SPRITE_INDEX: usize = 22;
+const MAX_FRUIT_ICONS: u32 = 7;
+
+/// The "1UP" or "2UP" above the score of the player, blinking during their turn.
+#[derive(Component)]
+pub struct UpText(pub usize);
+
+/// The score of the player.
+#[derive(Component)]
+pub struct ScoreText(pub usize);
+
+#[derive(Component)]
+pub struct HighScoreText;
+
+#[derive(Component)]
+pub struct LifeIcon;
+
+#[derive(Component)]
+pub struct FruitIcon;
+
+#[derive(Component, Deref, DerefMut)]
+pub struct BlinkTimer(pub Timer);
+
+/// Spawns a HUD text with its [anchor] at the top left corner of [tile] (in maze tiles).
+fn hud_text(
+    text: &str,
+    tile: TilePos,
+    anchor: Anchor,
+    (font, colour): &(TextFont, TextColor),
+) -> impl Bundle {
+    let mut translation = tile.to_maze_display_pos().with_z(HUD_Z);
+    if anchor == Anchor::TopRight {
+        translation.x += TILE_SIZE as f32;
+    }
+
+    (
+        Text2d::new(text),
+        font.clone(),
+        *colour,
+        anchor,
+        Transform::from_translation(translation),
+    )
+}
+
+pub fn setup_hud(mut commands: Commands, asset_server: Res<AssetServer>, theme: Res<Theme>) {
+    let font = (
+        TextFont {
+            font: asset_server.load(&theme.font),
+            font_size: TILE_SIZE as f32,
+            font_smoothing: FontSmoothing::None,
+            ..default()
+        },
+        TextColor(theme.colours.text()),
+    );
+
+    for (player, (up_x, score_x)) in [(3, 6), (22, 25)].into_iter().enumerate() {
+        commands.spawn((
+            hud_text(
+                &format!("{}UP", player + 1),
+                TilePos { x: up_x, y: -3 },
+                Anchor::TopLeft,
+                &font,
+            ),
+            UpText(player),
+            BlinkTimer(Timer::from_seconds(UP_BLINK_SECONDS, TimerMode::Repeating)),
+        ));
+
+        commands.spawn((
+            hud_text("00", TilePos { x: score_x, y: -2 }, Anchor::TopRight, &font),
+            ScoreText(player),
+        ));
+    }
+
+    commands.spawn(hud_text(
+        "HIGH SCORE",
+        TilePos { x: 9, y: -3 },
+        Anchor::TopLeft,
+        &font,
+    ));
+
+    commands.spawn((
+        hud_text("", TilePos { x: 16, y: -2 }, Anchor::TopRight, &font),
+        HighScoreText,
+    ));
+}
+
+/// Blinks the "UP" of the player whose turn it is, the UP of players not in the game is hidden.
+pub fn blink_up_text(
+    time: Res<Time>,
+    players: Res<Players>,
+    up_texts: Query<(&UpText, &mut Visibility, &mut BlinkTimer)>,
+) {
+    for (up_text, mut visibility, mut timer) in up_texts {
+        if up_text.0 >= players.count {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+
+        if up_text.0 != players.current {
+            *visibility = Visibility::Inherited;
+            continue;
+        }
+
+        timer.tick(time.delta());
+        if timer.just_finished() {
+            visibility.toggle_visible_hidden();
+        }
+    }
+}
+
+pub fn update_score_text(
+    score: Res<Score>,
+    players: Res<Players>,
+    score_texts: Query<(&ScoreText, &mut Text2d)>,
+) {
+    for (score_text, mut text) in score_texts {
+        text.0 = if score_text.0 < players.count {
+            format!("{:02}", players.score_of(score_text.0, &score))
+        } else {
+            "".into()
+        };
+    }
+}
+
+pub fn update_high_score_text(
+    score: Res<Score>,
+    level: Res<Level>,
+    players: Res<Players>,
+    high_scores: Res<HighScores>,
+    mut high_score_text: Single<&mut Text2d, With<HighScoreText>>,
+) {
+    let (best_score, _) = players.best_result(&score, &level);
+    let high_score = high_scores.top_score().max(best_score);
+    high_score_text.0 = if high_score > 0 {
+        format!("{:02}", high_score)
+    } else {
+        "".into()
+    };
+}
+
+/// Shows the lives in reserve as Pac-Man icons below the maze.
+pub fn update_life_icons(
+    mut commands: Commands,
+    lives: Res<Lives>,
+    sprites: Res<CharacterSprites>,
+    icons: Query<Entity, With<LifeIcon>>,
+) {
+    if !lives.is_changed() {
+        return;
+    }
+
+    for icon in icons {
+        commands.entity(icon).despawn();
+    }
+
+    for i in 0..lives.saturating_sub(1) {
+        let tile_pos = TilePos {
+            x: 2 + 2 * i as i32,
+            y: 31,
+        };
+
+        let mut sprite = sprites.sprite(LIFE_ICON_SPRITE_INDEX);
+        sprite.anchor = Anchor::TopLeft;
+
+        commands.spawn((
+            LifeIcon,
+            sprite,
+            Transform::from_translation(tile_pos.to_maze_display_pos().with_z(HUD_Z)),
+        ));
+    }
+}
+
+/// Shows the fruit of the current and up to six previous levels in the bottom right corner,
+/// the current level's fruit furthest to the right.
+pub fn update_fruit_icons(
+    mut commands: Commands,
+    level: Res<Level>,
+    sprites: Res<CharacterSprites>,
+    icons: Query<Entity, With<FruitIcon>>,
+) {
+    if !level.is_changed() {
+        return;
+    }
+
+    for icon in icons {
+        commands.entity(icon).despawn();
+    }
+
+    let first_level = level.saturating_sub(MAX_FRUIT_ICONS - 1).max(1);
+    for (i, shown_level) in (first_level..=**level).rev().enumerate() {
+        let tile_pos = TilePos {
+            x: MAP_WIDTH as i32 - 4 - 2 * i as i32,
+            y: 31,
+        };
+
+        let mut sprite = sprites.sprite(Fruit::for_level(shown_level).sprite_index());
+        sprite.anchor = Anchor::TopLeft;
+
+        commands.spawn((
+            FruitIcon,
+            sprite,
+            Transform::from_translation(tile_pos.to_maze_display_pos().with_z(HUD_Z)),
+        ));
+    }
+}
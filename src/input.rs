@@ -0,0 +1,219 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Direction, load_ron_or};
+
+pub const BINDINGS_PATH: &str = "assets/config/bindings.ron";
+/// Used when the bindings can't be loaded.
+const DEFAULT_BINDINGS: &str = include_str!("../assets/config/bindings.ron");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Pause,
+    Confirm,
+    ToggleZoomMode,
+    ToggleDebug,
+    CycleGhostMode,
+    VolumeUp,
+    VolumeDown,
+    ToggleMute,
+    /// Starts a two player game from the title screen.
+    StartTwoPlayers,
+    /// Starts a game with two Pac-Men playing together from the title screen.
+    StartCoop,
+    /// Starts a game with the second player steering a ghost from the title screen.
+    StartVersus,
+    /// Steer the second Pac-Man in a co-op game, or the ghost in versus mode.
+    P2MoveUp,
+    P2MoveDown,
+    P2MoveLeft,
+    P2MoveRight,
+    /// Saves the game to a file, to resume it later with [Action::LoadGame].
+    SaveGame,
+    LoadGame,
+    /// Saves to the debug slot, in debug mode.
+    SaveSlot,
+    /// Goes back to the debug slot, in debug mode.
+    LoadSlot,
+    /// Steps the game backwards while held, in debug mode.
+    Rewind,
+    /// Freezes the simulation, in debug mode.
+    FreezeSimulation,
+    /// Advances a frozen simulation by one fixed frame, in debug mode.
+    StepFrame,
+    /// Changes the simulation speed, in debug mode.
+    SlowDown,
+    SpeedUp,
+    /// Opens the debug console, in debug mode.
+    OpenConsole,
+}
+
+impl Action {
+    /// The movement actions and the direction they steer in.
+    pub const MOVEMENT: [(Action, Direction); 4] = [
+        (Action::MoveUp, Direction::Up),
+        (Action::MoveLeft, Direction::Left),
+        (Action::MoveDown, Direction::Down),
+        (Action::MoveRight, Direction::Right),
+    ];
+
+    /// The movement actions of the second player in a co-op game.
+    pub const P2_MOVEMENT: [(Action, Direction); 4] = [
+        (Action::P2MoveUp, Direction::Up),
+        (Action::P2MoveLeft, Direction::Left),
+        (Action::P2MoveDown, Direction::Down),
+        (Action::P2MoveRight, Direction::Right),
+    ];
+
+    /// The movement actions of [player].
+    pub fn movement(player: usize) -> &'static [(Action, Direction); 4] {
+        if player == 0 {
+            &Self::MOVEMENT
+        } else {
+            &Self::P2_MOVEMENT
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    GamepadButton(GamepadButton),
+    /// The left stick pushed in the direction, past the deadzone.
+    LeftStick(Direction),
+}
+
+#[derive(Resource, Serialize, Deserialize)]
+pub struct InputBindings {
+    /// How far the stick has to be pushed (0.0 - 1.0) before it counts.
+    pub stick_deadzone: f32,
+    pub actions: HashMap<Action, Vec<Binding>>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        ron::from_str(DEFAULT_BINDINGS).expect("The default input bindings should be valid")
+    }
+}
+
+impl InputBindings {
+    /// Loads the bindings from [path], falling back to the default bindings if it can't be read.
+    pub fn load_or_default(path: &str) -> Self {
+        load_ron_or(path, "input bindings", Self::default)
+    }
+
+    /// The first key bound to [action], named for hints like "T" or "BracketLeft".
+    pub fn key_name(&self, action: Action) -> String {
+        let key =
+            self.actions
+                .get(&action)
+                .into_iter()
+                .flatten()
+                .find_map(|binding| match binding {
+                    Binding::Key(key) => Some(key),
+                    _ => None,
+                });
+
+        let Some(key) = key else {
+            return "unbound".into();
+        };
+
+        let name = format!("{key:?}");
+        name.strip_prefix("Key")
+            .or_else(|| name.strip_prefix("Digit"))
+            .unwrap_or(&name)
+            .to_string()
+    }
+
+    fn is_pressed(
+        &self,
+        binding: &Binding,
+        keyboard_input: &ButtonInput<KeyCode>,
+        gamepads: &Query<&Gamepad>,
+    ) -> bool {
+        match binding {
+            Binding::Key(key) => keyboard_input.pressed(*key),
+            Binding::GamepadButton(button) => gamepads.iter().any(|g| g.pressed(*button)),
+            Binding::LeftStick(dir) => gamepads.iter().any(|g| {
+                let stick = g.left_stick();
+                let amount = match dir {
+                    Direction::Up => stick.y,
+                    Direction::Right => stick.x,
+                    Direction::Down => -stick.y,
+                    Direction::Left => -stick.x,
+                };
+
+                // Only count the dominant axis so diagonals don't press two directions at once.
+                amount > self.stick_deadzone && amount >= stick.x.abs().max(stick.y.abs())
+            }),
+        }
+    }
+}
+
+/// The actions currently held, gathered from every input device each frame.
+#[derive(Resource, Default)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    /// Whether the keys are being used for something else this frame, like typing.
+    blocked: bool,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        !self.blocked && self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn any_just_pressed(&self) -> bool {
+        !self.just_pressed.is_empty()
+    }
+
+    /// Ignores every action for the rest of the frame. The held ones still don't count as
+    /// just pressed next frame.
+    pub fn block(&mut self) {
+        self.just_pressed.clear();
+        self.blocked = true;
+    }
+}
+
+/// A run condition for systems that only run when [action] was just pressed.
+pub fn action_just_pressed(action: Action) -> impl Fn(Res<ActionState>) -> bool + Clone {
+    move |actions: Res<ActionState>| actions.just_pressed(action)
+}
+
+/// A run condition for systems that run for as long as [action] is held.
+pub fn action_pressed(action: Action) -> impl Fn(Res<ActionState>) -> bool + Clone {
+    move |actions: Res<ActionState>| actions.pressed(action)
+}
+
+pub fn update_action_state(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    bindings: Res<InputBindings>,
+    mut state: ResMut<ActionState>,
+) {
+    let pressed: HashSet<Action> = bindings
+        .actions
+        .iter()
+        .filter(|(_, action_bindings)| {
+            action_bindings
+                .iter()
+                .any(|binding| bindings.is_pressed(binding, &keyboard_input, &gamepads))
+        })
+        .map(|(action, _)| *action)
+        .collect();
+
+    state.just_pressed = pressed.difference(&state.pressed).copied().collect();
+    state.pressed = pressed;
+    state.blocked = false;
+}
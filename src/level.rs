@@ -0,0 +1,93 @@
+use bevy::{ecs::system::RunSystemOnce, log, prelude::*};
+
+use crate::{
+    components::Ghost,
+    cutscene::Cutscenes,
+    death::{reset_ghosts, reset_positions},
+    fruit::DotsEaten,
+    game_state::GameState,
+    map::respawn_dots,
+    score::Scorable,
+};
+
+/// How long the cleared maze is shown before moving on.
+const LEVEL_COMPLETE_SECONDS: f32 = 2.;
+
+/// The level currently being played, starting at 1.
+#[derive(Resource, Deref, DerefMut)]
+pub struct Level(pub u32);
+
+impl Default for Level {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+#[derive(Resource, Deref, DerefMut)]
+pub struct LevelCompleteTimer(Timer);
+
+/// The level is cleared once every dot and energizer has been eaten.
+pub fn check_level_cleared(
+    scorables: Query<&Scorable>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let cleared = !scorables
+        .iter()
+        .any(|s| matches!(s, Scorable::Dot | Scorable::Energizer));
+
+    if cleared {
+        next_state.set(GameState::LevelComplete);
+    }
+}
+
+pub fn start_level_complete(
+    mut commands: Commands,
+    mut ghosts: Query<&mut Visibility, With<Ghost>>,
+) {
+    for mut visibility in &mut ghosts {
+        *visibility = Visibility::Hidden;
+    }
+
+    commands.insert_resource(LevelCompleteTimer(Timer::from_seconds(
+        LEVEL_COMPLETE_SECONDS,
+        TimerMode::Once,
+    )));
+}
+
+/// Moves on to the next level, by way of an intermission after some levels.
+pub fn update_level_complete(
+    time: Res<Time>,
+    mut timer: ResMut<LevelCompleteTimer>,
+    level: Res<Level>,
+    cutscenes: Res<Cutscenes>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !timer.tick(time.delta()).finished() {
+        return;
+    }
+
+    if cutscenes.intermission_after(**level).is_some() {
+        next_state.set(GameState::Intermission);
+    } else {
+        next_state.set(GameState::Playing);
+    }
+}
+
+pub fn advance_level(mut level: ResMut<Level>) {
+    **level += 1;
+}
+
+/// Sets the maze, the characters and the ghosts up for the level in [Level], whether it was
+/// reached by clearing the previous one or jumped to from the console.
+pub fn start_level(world: &mut World) {
+    log::info!("Starting level {}", **world.resource::<Level>());
+    **world.resource_mut::<DotsEaten>() = 0;
+
+    let started = world
+        .run_system_once(respawn_dots)
+        .and_then(|()| world.run_system_once(reset_positions))
+        .and_then(|()| world.run_system_once(reset_ghosts));
+    if let Err(err) = started {
+        log::error!("Failed to start the level ({err})");
+    }
+}
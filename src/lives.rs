@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+
+use crate::{events::ScoreMilestoneEvent, score::Milestone};
+
+pub const STARTING_LIVES: u32 = 3;
+
+/// The lives left, including the one currently being played.
+#[derive(Resource, Deref, DerefMut)]
+pub struct Lives(pub u32);
+
+impl Default for Lives {
+    fn default() -> Self {
+        Self(STARTING_LIVES)
+    }
+}
+
+pub fn award_extra_life(
+    mut lives: ResMut<Lives>,
+    mut milestone_events: EventReader<ScoreMilestoneEvent>,
+) {
+    for event in milestone_events.read() {
+        if event.milestone != Milestone::ExtraLife {
+            continue;
+        }
+
+        **lives += 1;
+    }
+}
@@ -1,42 +1,137 @@
-use bevy::{prelude::*, window::PresentMode};
-use common::{PixelPos, TilePos};
-use components::{AnimationIndices, AnimationTimer, Movable};
-use ghosts::blinky::blinky_update_target;
-use map::spawn_map;
-use player::{control_player, eat, pacman_bundle, player_take_move_decision};
-use score::Score;
-use ui::{setup_ui, update_score_text};
+use animation::{AnimationLibrary, animate_sprite, select_animation_clips};
+use attract::{
+    clear_previous_game, leave_game_over, run_if_demo, setup_title_screen, start_game_over_timer,
+    start_new_game, steer_demo_pacman, update_demo, update_title_screen,
+};
+use audio::{
+    AUDIO_SETTINGS_PATH, AudioSettings, Sounds, apply_audio_settings, change_volume,
+    pause_background_loop, play_death_sound, play_eat_sounds, play_extra_life_sound,
+    play_intro_jingle, stop_background_loop, update_background_loop,
+};
+use bevy::{
+    input::InputSystem,
+    prelude::*,
+    time::TimeSystem,
+    window::{PresentMode, WindowResized},
+};
+use camera::{
+    CameraSettings, DEFAULT_WINDOW_SIZE, fit_camera_to_window, setup_camera, toggle_zoom_mode,
+};
+use common::CharacterSprites;
+use console::{
+    Console, block_actions_while_typing, close_console, open_console, run_console_command,
+    run_if_console_open, setup_console, type_in_console, update_console_text,
+};
+use cutscene::{
+    CUTSCENES_PATH, CutscenePlayer, Cutscenes, add_cutscene_sprites, play_cutscene,
+    start_intermission,
+};
+use death::{
+    catch_pacman, lose_life, reset_ghosts, reset_positions, run_if_shrivelling,
+    update_death_sequence,
+};
+use fruit::{
+    DotsEaten, add_wide_points_sprites, despawn_bonus_fruit, spawn_bonus_fruit,
+    steer_wandering_fruit,
+};
+use game_state::GameState;
+use high_score::{
+    HIGH_SCORES_PATH, HighScores, handle_game_over, setup_game_over_screen, setup_initials_entry,
+    update_initials_entry,
+};
+use hud::{
+    blink_up_text, setup_hud, update_fruit_icons, update_high_score_text, update_life_icons,
+    update_score_text,
+};
+use input::{
+    Action, ActionState, BINDINGS_PATH, InputBindings, action_just_pressed, action_pressed,
+    update_action_state,
+};
+use level::{
+    Level, advance_level, check_level_cleared, start_level, start_level_complete,
+    update_level_complete,
+};
+use lives::{Lives, award_extra_life};
+use map::{respawn_dots, spawn_map};
+use pause::{PauseRes, run_if_not_paused, toggle_pause};
+use player::{control_player, eat, eat_ghosts, pacman_bundle, player_take_move_decision};
+use players::{Players, restore_player_dots, spawn_coop_pacman, switch_player};
+use popup::{run_if_not_frozen, spawn_score_popups, update_score_popups};
+use rewind::{RewindBuffer, record_frame, scrub, setup_scrub_bar, step_back, update_scrub_bar};
+use rng::GameRng;
+use rules::GameRules;
+use score::{Score, ScoreMilestones, check_score_milestones};
+use snapshot::{
+    PendingSnapshot, SnapshotSlot, apply_pending_snapshot, load_game, load_slot, save_game,
+    save_slot,
+};
+use theme::Theme;
+use ui::{setup_ui, update_paused_text};
 
 use crate::{
-    debug::{DebugRes, run_if_debug, toggle_debug_mode},
-    events::CharacterReachedTargetEvent,
+    debug::{
+        DebugRes, control_simulation, run_if_debug, run_if_simulation_advancing, step_simulation,
+        toggle_debug_mode,
+    },
+    events::{
+        AnimationFinishedEvent, CharacterReachedTargetEvent, GameOverEvent, PacmanDeathEvent,
+        ScorableEatenEvent, ScoreMilestoneEvent,
+    },
     ghosts::{
         GhostName,
-        clyde::{clyde_bundle, clyde_debug, clyde_update_target},
+        brain::{debug_ghost_brains, update_ghost_targets},
+        ghost_bundle,
         ghost_debug::{
             debug_plot_ghost_path, ghost_debug_bundle, ghost_mode_debug_update, update_ghost_debug,
         },
-        ghost_mode::GhostModeRes,
-        ghost_movement::{ghost_handle_scatter, ghost_movement},
-        inky::{inky_debug, inky_update_target},
-        pinky::pinky_update_target,
+        ghost_mode::{
+            FrightenedRes, GhostModeRes, start_frightened, update_frightened, update_ghost_mode,
+        },
+        ghost_movement::{
+            assign_ghost_controllers, control_ghosts, ghost_handle_eaten, ghost_handle_scatter,
+            ghost_movement,
+        },
     },
     movement::{move_character, visually_move_character},
     ui::update_debug_text,
 };
 
+pub mod animation;
+pub mod attract;
+pub mod audio;
+pub mod camera;
 pub mod common;
 pub mod components;
+pub mod console;
+pub mod cutscene;
+pub mod death;
 pub mod debug;
 pub mod events;
+pub mod fruit;
+pub mod game_state;
 pub mod ghosts;
+pub mod high_score;
+pub mod hud;
+pub mod input;
+pub mod level;
+pub mod lives;
 pub mod map;
 pub mod movement;
+pub mod pause;
 pub mod player;
+pub mod players;
+pub mod popup;
+pub mod rewind;
+pub mod rng;
+pub mod rules;
 pub mod score;
+pub mod snapshot;
+pub mod theme;
 pub mod ui;
 
 fn main() {
+    let theme = Theme::from_args();
+
     App::new()
         .add_plugins(
             DefaultPlugins
@@ -44,22 +139,187 @@ fn main() {
                 .set(WindowPlugin {
                     primary_window: Some(Window {
                         title: "Pacman by Vidde".into(),
-                        resolution: (3000., 1600.).into(),
+                        resolution: DEFAULT_WINDOW_SIZE.into(),
                         present_mode: PresentMode::AutoVsync,
                         ..default()
                     }),
                     ..default()
                 }),
         ) // prevents blurry sprites
-        .insert_resource(ClearColor(Color::BLACK))
+        .insert_resource(ClearColor(theme.colours.background()))
         .insert_resource(Score::new())
+        .insert_resource(Level::default())
+        .insert_resource(HighScores::load_or_default(HIGH_SCORES_PATH))
         .insert_resource(GhostModeRes::default())
+        .insert_resource(FrightenedRes::default())
+        .insert_resource(DotsEaten::default())
+        .insert_resource(GameRng::from_args())
         .insert_resource(DebugRes::default())
+        .insert_resource(GameRules::from_args())
+        .init_resource::<ScoreMilestones>()
+        .insert_resource(Lives::default())
+        .init_resource::<Players>()
+        .init_resource::<SnapshotSlot>()
+        .init_resource::<RewindBuffer>()
+        .init_resource::<Console>()
+        .insert_resource(PauseRes::default())
+        .insert_resource(CameraSettings::default())
+        .insert_resource(ActionState::default())
+        .insert_resource(InputBindings::load_or_default(BINDINGS_PATH))
+        .insert_resource(AnimationLibrary::load_or_default(&theme.animations))
+        .insert_resource(Cutscenes::load_or_default(CUTSCENES_PATH))
+        .insert_resource(theme)
+        .insert_resource(AudioSettings::load_or_default(AUDIO_SETTINGS_PATH))
+        .init_resource::<Sounds>()
+        .init_state::<GameState>()
+        .enable_state_scoped_entities::<GameState>()
         .add_event::<CharacterReachedTargetEvent>()
-        .add_systems(Startup, (setup_world, setup_ui))
+        .add_event::<GameOverEvent>()
+        .add_event::<ScoreMilestoneEvent>()
+        .add_event::<ScorableEatenEvent>()
+        .add_event::<AnimationFinishedEvent>()
+        .add_event::<PacmanDeathEvent>()
+        .add_systems(
+            Startup,
+            (
+                setup_camera,
+                setup_world,
+                setup_ui,
+                setup_hud,
+                setup_scrub_bar,
+                setup_console,
+                apply_audio_settings,
+            ),
+        )
+        .add_systems(PreUpdate, update_action_state.after(InputSystem))
+        .add_systems(
+            PreUpdate,
+            block_actions_while_typing
+                .after(update_action_state)
+                .run_if(run_if_console_open),
+        )
+        .add_systems(
+            Update,
+            (
+                type_in_console,
+                open_console
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(run_if_debug)
+                    .run_if(action_just_pressed(Action::OpenConsole)),
+                run_console_command.run_if(in_state(GameState::Playing)),
+                update_console_text,
+            )
+                .chain(),
+        )
+        .add_systems(OnEnter(GameState::Title), setup_title_screen)
+        .add_systems(
+            OnTransition {
+                exited: GameState::Title,
+                entered: GameState::Playing,
+            },
+            (
+                (
+                    start_new_game,
+                    clear_previous_game,
+                    spawn_coop_pacman,
+                    assign_ghost_controllers,
+                    respawn_dots,
+                    reset_positions,
+                    reset_ghosts,
+                )
+                    .chain(),
+                play_intro_jingle.run_if(not(run_if_demo)),
+            ),
+        )
+        .add_systems(OnEnter(GameState::LevelComplete), start_level_complete)
+        .add_systems(OnEnter(GameState::Intermission), start_intermission)
+        .add_systems(
+            OnTransition {
+                exited: GameState::LevelComplete,
+                entered: GameState::Playing,
+            },
+            (advance_level, start_level).chain(),
+        )
+        .add_systems(
+            OnTransition {
+                exited: GameState::Intermission,
+                entered: GameState::Playing,
+            },
+            (advance_level, start_level).chain(),
+        )
+        .add_systems(
+            OnExit(GameState::Playing),
+            (stop_background_loop, close_console),
+        )
+        .add_systems(
+            OnTransition {
+                exited: GameState::Dying,
+                entered: GameState::Playing,
+            },
+            (
+                switch_player,
+                restore_player_dots,
+                reset_positions,
+                reset_ghosts,
+            )
+                .chain(),
+        )
+        .add_systems(OnEnter(GameState::EnterInitials), setup_initials_entry)
+        .add_systems(
+            OnEnter(GameState::GameOver),
+            (setup_game_over_screen, start_game_over_timer),
+        )
+        .add_systems(
+            Update,
+            (
+                update_title_screen.run_if(in_state(GameState::Title)),
+                update_demo.run_if(run_if_demo),
+                update_initials_entry.run_if(in_state(GameState::EnterInitials)),
+                leave_game_over.run_if(in_state(GameState::GameOver)),
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                save_game
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(action_just_pressed(Action::SaveGame)),
+                load_game
+                    .after(update_title_screen)
+                    .run_if(in_state(GameState::Title).or(in_state(GameState::Playing)))
+                    .run_if(not(run_if_demo))
+                    .run_if(action_just_pressed(Action::LoadGame)),
+                (
+                    save_slot.run_if(action_just_pressed(Action::SaveSlot)),
+                    load_slot.run_if(action_just_pressed(Action::LoadSlot)),
+                )
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(run_if_debug),
+                (apply_pending_snapshot, visually_move_character)
+                    .chain()
+                    .before(move_character)
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(resource_exists::<PendingSnapshot>),
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                record_frame
+                    .after(update_ghost_targets)
+                    .run_if(run_if_not_paused)
+                    .run_if(run_if_not_frozen)
+                    .run_if(run_if_simulation_advancing),
+                (step_back.run_if(action_pressed(Action::Rewind)), scrub)
+                    .before(apply_pending_snapshot),
+            )
+                .run_if(in_state(GameState::Playing))
+                .run_if(run_if_debug),
+        )
+        .add_systems(Update, update_scrub_bar)
         .add_systems(
             FixedUpdate,
-            (debug_plot_ghost_path, inky_debug, clyde_debug).run_if(run_if_debug),
+            (debug_plot_ghost_path, debug_ghost_brains).run_if(run_if_debug),
         )
         .add_systems(
             Update,
@@ -70,25 +330,116 @@ fn main() {
             )
                 .run_if(run_if_debug),
         )
+        .add_systems(
+            Update,
+            (toggle_debug_mode, toggle_pause, update_paused_text).chain(),
+        )
+        .add_systems(
+            Update,
+            control_simulation
+                .after(toggle_debug_mode)
+                .run_if(run_if_debug),
+        )
+        .add_systems(First, step_simulation.after(TimeSystem))
+        .add_systems(
+            Update,
+            (
+                change_volume,
+                pause_background_loop.run_if(resource_changed::<PauseRes>),
+                (
+                    play_eat_sounds,
+                    play_extra_life_sound,
+                    play_death_sound,
+                    update_background_loop
+                        .run_if(in_state(GameState::Playing))
+                        .run_if(run_if_not_paused),
+                )
+                    .run_if(not(run_if_demo)),
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                toggle_zoom_mode,
+                fit_camera_to_window
+                    .run_if(on_event::<WindowResized>.or(resource_changed::<CameraSettings>)),
+            )
+                .chain(),
+        )
+        .add_systems(
+            Update,
+            (
+                update_score_text,
+                update_high_score_text,
+                update_life_icons,
+                update_fruit_icons,
+                blink_up_text,
+            ),
+        )
         .add_systems(
             Update,
             (
-                toggle_debug_mode,
-                animate_sprite,
-                control_player,
+                (select_animation_clips, animate_sprite).chain(),
+                (
+                    (control_player, control_ghosts).run_if(not(run_if_demo)),
+                    steer_demo_pacman.run_if(run_if_demo),
+                ),
                 move_character,
                 visually_move_character,
                 player_take_move_decision,
                 ghost_movement,
                 ghost_handle_scatter,
-                eat,
-                update_score_text,
-                blinky_update_target,
-                pinky_update_target,
-                inky_update_target,
-                clyde_update_target,
+                ghost_handle_eaten,
+                (eat, eat_ghosts, catch_pacman, check_level_cleared).chain(),
+                (start_frightened, update_frightened, update_ghost_mode).chain(),
+                (
+                    spawn_bonus_fruit,
+                    steer_wandering_fruit,
+                    despawn_bonus_fruit,
+                )
+                    .chain(),
+                check_score_milestones,
+                award_extra_life,
+                update_ghost_targets,
             )
-                .chain(),
+                .chain()
+                .run_if(in_state(GameState::Playing))
+                .run_if(run_if_not_paused)
+                .run_if(run_if_not_frozen),
+        )
+        .add_systems(
+            Update,
+            (
+                animate_sprite.run_if(run_if_shrivelling),
+                update_death_sequence,
+                lose_life,
+                handle_game_over,
+            )
+                .chain()
+                .run_if(in_state(GameState::Dying))
+                .run_if(run_if_not_paused),
+        )
+        .add_systems(
+            Update,
+            update_level_complete
+                .run_if(in_state(GameState::LevelComplete))
+                .run_if(run_if_not_paused),
+        )
+        .add_systems(
+            Update,
+            (animate_sprite, play_cutscene)
+                .chain()
+                .run_if(in_state(GameState::Intermission))
+                .run_if(resource_exists::<CutscenePlayer>)
+                .run_if(run_if_not_paused),
+        )
+        .add_systems(
+            Update,
+            (spawn_score_popups, update_score_popups)
+                .chain()
+                .after(eat_ghosts)
+                .run_if(in_state(GameState::Playing))
+                .run_if(run_if_not_paused),
         )
         .run();
 }
@@ -98,6 +449,8 @@ fn setup_world(
     mut config_store: ResMut<GizmoConfigStore>,
     assert_server: Res<AssetServer>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    animations: Res<AnimationLibrary>,
+    theme: Res<Theme>,
 ) {
     config_store
         .config_mut::<DefaultGizmoConfigGroup>()
@@ -105,63 +458,52 @@ fn setup_world(
         .line
         .width = 3.0;
 
-    let camera_pos: PixelPos = TilePos { x: 14, y: 15 }.into();
-
-    commands.spawn((
-        Camera2d,
-        Projection::Orthographic(OrthographicProjection {
-            scale: 0.4,
-            ..OrthographicProjection::default_2d()
-        }),
-        Transform::from_translation(Vec3::new(camera_pos.x as f32, -camera_pos.y as f32, 0.)),
-    ));
+    spawn_characters(
+        &mut commands,
+        &assert_server,
+        &mut texture_atlas_layouts,
+        &animations,
+        &theme,
+    );
 
-    spawn_characters(&mut commands, &assert_server, &mut texture_atlas_layouts);
-
-    spawn_map(&mut commands, &assert_server, &mut texture_atlas_layouts);
+    spawn_map(
+        &mut commands,
+        &assert_server,
+        &mut texture_atlas_layouts,
+        &theme,
+    );
 }
 
 fn spawn_characters(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
     texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
+    animations: &AnimationLibrary,
+    theme: &Theme,
 ) {
-    let texture = asset_server.load("sprites/pacman_spritesheet_2.png");
-    let layout =
-        TextureAtlasLayout::from_grid(UVec2::splat(16), 14, 13, None, Some(UVec2 { x: 456, y: 0 }));
+    let texture = asset_server.load(&theme.spritesheet);
+    let mut layout = animations.grid.layout();
+    add_wide_points_sprites(&mut layout);
+    add_cutscene_sprites(&mut layout);
     let texture_atlas_layout = texture_atlas_layouts.add(layout);
 
-    commands.spawn(pacman_bundle(texture.clone(), texture_atlas_layout.clone()));
-
-    // commands.spawn(blinky_bundle(texture.clone(), texture_atlas_layout.clone()));
-    // commands.spawn(ghost_debug_bundle(GhostName::Blinky));
-
-    // commands.spawn(pinky_bundle(texture.clone(), texture_atlas_layout.clone()));
-    // commands.spawn(ghost_debug_bundle(GhostName::Pinky));
-
-    // commands.spawn(inky_bundle(texture.clone(), texture_atlas_layout.clone()));
-    // commands.spawn(ghost_debug_bundle(GhostName::Inky));
+    commands.spawn(pacman_bundle(
+        texture.clone(),
+        texture_atlas_layout.clone(),
+        animations,
+        0,
+    ));
 
-    commands.spawn(clyde_bundle(texture.clone(), texture_atlas_layout.clone()));
+    commands.spawn(ghost_bundle(
+        GhostName::Clyde,
+        texture.clone(),
+        texture_atlas_layout.clone(),
+        animations,
+    ));
     commands.spawn(ghost_debug_bundle(GhostName::Clyde));
-}
-
-fn animate_sprite(
-    time: Res<Time>,
-    mut query: Query<(
-        &mut AnimationIndices,
-        &mut AnimationTimer,
-        &mut Sprite,
-        &Movable,
-    )>,
-) {
-    for (mut indices, mut timer, mut sprite, movable) in &mut query {
-        timer.tick(time.delta());
 
-        if timer.just_finished() {
-            if let Some(atlas) = &mut sprite.texture_atlas {
-                atlas.index = indices.next(&movable.direction)
-            }
-        }
-    }
+    commands.insert_resource(CharacterSprites {
+        texture,
+        layout: texture_atlas_layout,
+    });
 }
@@ -1,9 +1,11 @@
 use bevy::{prelude::*, sprite::Anchor};
+use serde::Deserialize;
 
 use crate::{
     common::{Direction, TilePos},
     components::{GhostDoor, Position, Wall},
     score::Scorable,
+    theme::Theme,
 };
 
 pub const MAP_WIDTH: usize = 28;
@@ -28,7 +30,7 @@ pub enum OpenContent {
     Energizer,
 }
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Deserialize)]
 pub enum WallType {
     Straight(Direction),
     DoubleStraight(Direction),
@@ -44,7 +46,7 @@ pub enum WallType {
     NestEntranceRightEdge,
 }
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Deserialize)]
 pub enum Corner {
     TopRight,
     TopLeft,
@@ -144,15 +146,28 @@ impl Map {
     }
 }
 
+/// The spritesheet and 8x8 atlas layout of the maze tiles.
+#[derive(Resource, Clone)]
+pub struct MazeSprites {
+    pub texture: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+}
+
 pub fn spawn_map(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
     texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
+    theme: &Theme,
 ) {
-    let texture = asset_server.load("sprites/pacman_spritesheet_2.png");
-    let layout = TextureAtlasLayout::from_grid(UVec2::splat(8), 28, 31, None, None);
+    let texture = asset_server.load(&theme.spritesheet);
+    let layout = theme.maze_grid.layout();
     let texture_atlas_layout = texture_atlas_layouts.add(layout);
 
+    commands.insert_resource(MazeSprites {
+        texture: texture.clone(),
+        layout: texture_atlas_layout.clone(),
+    });
+
     // Spawn maze
     MAP.iter().enumerate().for_each(|(row_num, row)| {
         row.iter().enumerate().for_each(|(col_num, tile)| {
@@ -161,29 +176,103 @@ pub fn spawn_map(
 
             match tile {
                 MapType::Wall(wall_type) => {
-                    spawn_wall(commands, x, y, wall_type, &texture, &texture_atlas_layout);
-                }
-                MapType::GhostOnlyBarrier => {
-                    spawn_ghost_only_barrier(commands, x, y, &texture, &texture_atlas_layout)
+                    let sprite_index = theme.wall_sprite_index(wall_type);
+                    spawn_wall(
+                        commands,
+                        x,
+                        y,
+                        sprite_index,
+                        &texture,
+                        &texture_atlas_layout,
+                    );
                 }
+                MapType::GhostOnlyBarrier => spawn_ghost_only_barrier(
+                    commands,
+                    x,
+                    y,
+                    theme.ghost_door,
+                    &texture,
+                    &texture_atlas_layout,
+                ),
                 MapType::Open(open_type) => {
-                    spawn_open(commands, x, y, &open_type, &texture, &texture_atlas_layout);
+                    spawn_open(
+                        commands,
+                        x,
+                        y,
+                        open_type,
+                        theme,
+                        &texture,
+                        &texture_atlas_layout,
+                    );
                 }
             };
         })
     })
 }
 
+/// The tiles of every dot and energizer in a full maze.
+pub fn dot_tiles() -> Vec<TilePos> {
+    MAP.iter()
+        .enumerate()
+        .flat_map(|(row_num, row)| {
+            row.iter().enumerate().filter_map(move |(col_num, tile)| {
+                matches!(
+                    tile,
+                    MapType::Open(OpenContent::Food | OpenContent::Energizer)
+                )
+                .then_some(TilePos {
+                    x: col_num as i32,
+                    y: row_num as i32,
+                })
+            })
+        })
+        .collect()
+}
+
+/// Spawns the dots and energizers on [tiles].
+pub fn spawn_dots(
+    commands: &mut Commands,
+    sprites: &MazeSprites,
+    theme: &Theme,
+    tiles: &[TilePos],
+) {
+    for tile in tiles {
+        if let MapType::Open(open_type) = MAP.get_at(tile) {
+            spawn_open(
+                commands,
+                tile.x,
+                tile.y,
+                &open_type,
+                theme,
+                &sprites.texture,
+                &sprites.layout,
+            );
+        }
+    }
+}
+
+/// Puts every dot and energizer back in the maze, clearing whatever was left to eat.
+pub fn respawn_dots(
+    mut commands: Commands,
+    sprites: Res<MazeSprites>,
+    theme: Res<Theme>,
+    scorables: Query<Entity, With<Scorable>>,
+) {
+    for entity in &scorables {
+        commands.entity(entity).despawn();
+    }
+
+    spawn_dots(&mut commands, &sprites, &theme, &dot_tiles());
+}
+
 fn spawn_wall(
     commands: &mut Commands,
     x: i32,
     y: i32,
-    wall_type: &WallType,
+    sprite_index: usize,
     texture: &Handle<Image>,
     texture_atlas_layout: &Handle<TextureAtlasLayout>,
 ) {
-    let sprite_index = sprite_index_for_wall_type(wall_type);
-
     let tile_pos = TilePos { x, y };
     let visual_pos = tile_pos.to_maze_display_pos();
 
@@ -208,6 +297,7 @@ fn spawn_ghost_only_barrier(
     commands: &mut Commands,
     x: i32,
     y: i32,
+    sprite_index: usize,
     texture: &Handle<Image>,
     texture_atlas_layout: &Handle<TextureAtlasLayout>,
 ) {
@@ -218,7 +308,7 @@ fn spawn_ghost_only_barrier(
         texture.clone(),
         TextureAtlas {
             layout: texture_atlas_layout.clone(),
-            index: 350,
+            index: sprite_index,
         },
     );
     sprite.anchor = Anchor::TopLeft;
@@ -237,13 +327,14 @@ fn spawn_open(
     x: i32,
     y: i32,
     open_content: &OpenContent,
+    theme: &Theme,
     texture: &Handle<Image>,
     texture_atlas_layout: &Handle<TextureAtlasLayout>,
 ) {
     let (scorable, sprite_index) = match open_content {
         OpenContent::None => return,
-        OpenContent::Food => (Scorable::Dot, 29),
-        OpenContent::Energizer => (Scorable::Energizer, 85),
+        OpenContent::Food => (Scorable::Dot, theme.dot),
+        OpenContent::Energizer => (Scorable::Energizer, theme.energizer),
     };
 
     let tile_pos = TilePos { x, y };
@@ -267,44 +358,6 @@ fn spawn_open(
     ));
 }
 
-pub fn sprite_index_for_wall_type(wall_type: &WallType) -> usize {
-    match wall_type {
-        WallType::Straight(Direction::Up) => 115,
-        WallType::Straight(Direction::Right) => 41,
-        WallType::Straight(Direction::Down) => 59,
-        WallType::Straight(Direction::Left) => 42,
-        WallType::DoubleStraight(Direction::Up) => 1,
-        WallType::DoubleStraight(Direction::Right) => 55,
-        WallType::DoubleStraight(Direction::Down) => 253,
-        WallType::DoubleStraight(Direction::Left) => 28,
-        WallType::DoubleCorner(Corner::TopRight) => 27,
-        WallType::DoubleCorner(Corner::BottomRight) => 279,
-        WallType::DoubleCorner(Corner::BottomLeft) => 252,
-        WallType::DoubleCorner(Corner::TopLeft) => 0,
-        WallType::VerticalLineInnerCorner(Corner::TopRight) => 727,
-        WallType::VerticalLineInnerCorner(Corner::BottomRight) => 699,
-        WallType::VerticalLineInnerCorner(Corner::BottomLeft) => 672,
-        WallType::VerticalLineInnerCorner(Corner::TopLeft) => 700,
-        WallType::HorizontalLineInnerCornerTopRight => 13,
-        WallType::HorizontalLineInnerCornerTopLeft => 14,
-        WallType::OuterCorner(Corner::TopRight) => 125,
-        WallType::OuterCorner(Corner::BottomRight) => 58,
-        WallType::OuterCorner(Corner::BottomLeft) => 61,
-        WallType::OuterCorner(Corner::TopLeft) => 126,
-        WallType::InnerCorner(Corner::TopRight) => 209,
-        WallType::InnerCorner(Corner::BottomRight) => 271,
-        WallType::InnerCorner(Corner::BottomLeft) => 260,
-        WallType::InnerCorner(Corner::TopLeft) => 210,
-        WallType::NestCorner(Corner::TopRight) => 458,
-        WallType::NestCorner(Corner::BottomRight) => 346,
-        WallType::NestCorner(Corner::BottomLeft) => 353,
-        WallType::NestCorner(Corner::TopLeft) => 465,
-        WallType::NestEntranceLeftEdge => 348,
-        WallType::NestEntranceRightEdge => 351,
-        WallType::Inner => 280,
-    }
-}
-
 pub const MAP: Map = Map([
     // 0 (Upper wall)
     [
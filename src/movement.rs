@@ -7,15 +7,15 @@ use crate::{
     map::MAP,
 };
 
-const FULL_SPEED_PIXELS_PER_SECOND: f32 = 75.75757625;
+const FULL_SPEED_PIXELS_PER_SECOND: f32 = 75.757_576;
 const PAUSE_FRAME_TIME: f32 = 1. / 60.;
 
 pub fn move_character(
     time: Res<Time>,
-    mut query: Query<(&mut Movable, &mut Position, &Character)>,
+    mut query: Query<(Entity, &mut Movable, &mut Position, &Character)>,
     mut reach_target_event_writer: EventWriter<CharacterReachedTargetEvent>,
 ) {
-    for (mut movable, mut position, character) in query.iter_mut() {
+    for (entity, mut movable, mut position, character) in query.iter_mut() {
         if movable.pause_frames.is_some() {
             log::info!(
                 "{character} is pausing this frame ({} frames remaining) ",
@@ -30,10 +30,7 @@ pub fn move_character(
         }
 
         let tile_pos: TilePos = position.0.clone().into();
-        let mut has_reached_destination =
-            tile_pos == movable.target_tile && position.in_middle_of_tile();
-
-        if has_reached_destination {
+        if tile_pos == movable.target_tile && position.in_middle_of_tile() {
             // We're standing still
             continue;
         }
@@ -42,7 +39,8 @@ pub fn move_character(
 
         let time_per_pixel = 1. / (FULL_SPEED_PIXELS_PER_SECOND * movable.speed);
 
-        if movable.progress >= time_per_pixel {
+        // Fast movables, like eyes returning home, can cover several pixels in one frame.
+        while movable.progress >= time_per_pixel {
             movable.progress -= time_per_pixel;
             match movable.direction {
                 Direction::Up => position.y -= 1,
@@ -50,10 +48,22 @@ pub fn move_character(
                 Direction::Down => position.y += 1,
                 Direction::Left => position.x -= 1,
             }
+
+            if character == &Character::Pacman {
+                // Pac-Man keeps moving diagonally after cornering until he's back in the middle of the lane.
+                position.step_towards_lane_center(&movable.direction);
+            }
+
+            if TilePos::from(&position.0) == movable.target_tile && position.in_middle_of_tile() {
+                // A new target has to be decided before moving on.
+                break;
+            }
         }
 
         // Check if we have reached our destination now!
-        has_reached_destination = tile_pos == movable.target_tile && position.in_middle_of_tile();
+        let tile_pos: TilePos = (&position.0).into();
+        let has_reached_destination =
+            tile_pos == movable.target_tile && position.in_middle_of_tile();
 
         if has_reached_destination {
             log::info!(
@@ -62,33 +72,32 @@ pub fn move_character(
                 movable.direction
             );
             reach_target_event_writer.write(CharacterReachedTargetEvent::new(
+                entity,
                 character.clone(),
                 tile_pos.clone(),
             ));
             match movable.direction {
-                Direction::Right => {
-                    if tile_pos == MAP.right_tp_position() {
-                        let left_tp_pos = MAP.left_tp_position();
-                        position.0 = (&left_tp_pos).into();
-                        movable.target_tile = left_tp_pos.translate(&movable.direction);
+                // The wandering fruit leaves the maze through the tunnel instead of wrapping around.
+                _ if character == &Character::Fruit => {}
+                Direction::Right if tile_pos == MAP.right_tp_position() => {
+                    let left_tp_pos = MAP.left_tp_position();
+                    position.0 = (&left_tp_pos).into();
+                    movable.target_tile = left_tp_pos.translate(&movable.direction);
 
-                        log::info!(
-                            "{character} teleporting from {tile_pos:?} {:?} to {left_tp_pos:?}",
-                            movable.direction
-                        );
-                    }
+                    log::info!(
+                        "{character} teleporting from {tile_pos:?} {:?} to {left_tp_pos:?}",
+                        movable.direction
+                    );
                 }
-                Direction::Left => {
-                    if tile_pos == MAP.left_tp_position() {
-                        let right_tp_pos = MAP.right_tp_position();
-                        position.0 = (&right_tp_pos).into();
-                        movable.target_tile = right_tp_pos.translate(&movable.direction);
+                Direction::Left if tile_pos == MAP.left_tp_position() => {
+                    let right_tp_pos = MAP.right_tp_position();
+                    position.0 = (&right_tp_pos).into();
+                    movable.target_tile = right_tp_pos.translate(&movable.direction);
 
-                        log::info!(
-                            "{character} teleporting from {tile_pos:?} {:?} to {right_tp_pos:?}",
-                            movable.direction
-                        );
-                    }
+                    log::info!(
+                        "{character} teleporting from {tile_pos:?} {:?} to {right_tp_pos:?}",
+                        movable.direction
+                    );
                 }
                 _ => {}
             }
@@ -99,7 +108,7 @@ pub fn move_character(
 pub fn visually_move_character(query: Query<(&Position, &mut Transform), With<Movable>>) {
     for (position, mut transform) in query {
         let visual_pos = position.to_character_display_pos();
-        transform.translation.x = visual_pos.x as f32;
-        transform.translation.y = visual_pos.y as f32;
+        transform.translation.x = visual_pos.x;
+        transform.translation.y = visual_pos.y;
     }
 }
@@ -0,0 +1,18 @@
+use bevy::prelude::*;
+
+use crate::input::{Action, ActionState};
+
+#[derive(Resource, Default)]
+pub struct PauseRes {
+    pub paused: bool,
+}
+
+pub fn toggle_pause(mut pause: ResMut<PauseRes>, actions: Res<ActionState>) {
+    if actions.just_pressed(Action::Pause) {
+        pause.paused = !pause.paused;
+    }
+}
+
+pub fn run_if_not_paused(pause: Res<PauseRes>) -> bool {
+    !pause.paused
+}
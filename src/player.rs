@@ -1,25 +1,39 @@
-use bevy::{log, prelude::*, sprite::Anchor};
+use bevy::{prelude::*, sprite::Anchor};
 
 use crate::{
+    animation::{AnimationLibrary, direction_clip},
     common::{Character, Direction, PixelPos, TilePos},
-    components::{AnimationIndices, AnimationTimer, Movable, Player, Position, QueableDirection},
+    components::{Ghost, Movable, Player, Position, QueableDirection, SpawnPoint},
+    events::{CharacterReachedTargetEvent, ScorableEatenEvent},
+    ghosts::ghost_mode::GhostMode,
+    input::{Action, ActionState},
     map::MAP,
+    rules::GameRules,
     score::{Scorable, Score},
 };
 
+/// The one-shot clip of Pac-Man shrivelling up when caught.
+pub const DEATH_CLIP: &str = "death";
+/// Tells the second Pac-Man in a co-op game apart from the first.
+const SECOND_PACMAN_TINT: Color = Color::srgb(1., 0.6, 0.6);
+
+/// The Pac-Man steered by [player], the second one starts next to the first, heading the other way.
 pub fn pacman_bundle(
     texture: Handle<Image>,
     texture_atlas_layout: Handle<TextureAtlasLayout>,
+    animations: &AnimationLibrary,
+    player: usize,
 ) -> impl Bundle {
-    let mut pacman_indices = AnimationIndices::new(
-        vec![0, 1, 2, 1],
-        vec![14, 15, 2, 15],
-        vec![28, 29, 2, 29],
-        vec![42, 43, 2, 43],
-    );
+    let (start_tile, direction) = if player == 0 {
+        (TilePos { x: 14, y: 17 }, Direction::Right)
+    } else {
+        (TilePos { x: 13, y: 17 }, Direction::Left)
+    };
+
+    let animation = animations.animation("pacman", direction_clip(&direction));
 
-    let start_pos: PixelPos = TilePos { x: 14, y: 17 }.into();
-    let first_target = TilePos { x: 15, y: 17 };
+    let start_pos: PixelPos = start_tile.clone().into();
+    let first_target = start_tile.translate(&direction);
 
     let visual_start_pos = start_pos.to_character_display_pos();
 
@@ -27,79 +41,130 @@ pub fn pacman_bundle(
         texture,
         TextureAtlas {
             layout: texture_atlas_layout,
-            index: pacman_indices.next(&Direction::Right),
+            index: animation.index(),
         },
     );
     sprite.anchor = Anchor::TopLeft;
+    if player > 0 {
+        sprite.color = SECOND_PACMAN_TINT;
+    }
+
+    let position = Position(start_pos.clone());
+    let movable = Movable::new(first_target, direction, 0.8);
 
     (
         sprite,
         Character::Pacman,
-        Player,
+        Player { index: player },
         Transform::from_translation(visual_start_pos),
-        pacman_indices,
-        AnimationTimer(Timer::from_seconds(0.08, TimerMode::Repeating)),
-        Position(start_pos.clone()),
-        Movable::new(first_target, Direction::Right, 0.8),
-        QueableDirection {
-            next_direction: None,
-        },
+        animation,
+        SpawnPoint::new(&position, &movable),
+        position,
+        movable,
+        QueableDirection::default(),
     )
 }
 
 pub fn control_player(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut query: Query<(&Movable, &mut QueableDirection), With<Player>>,
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    rules: Res<GameRules>,
+    mut query: Query<(&Player, &Movable, &mut QueableDirection)>,
 ) {
-    for (movable, mut queue_dir) in &mut query {
-        let new_dir = if keyboard_input.just_pressed(KeyCode::KeyW)
-            || keyboard_input.just_pressed(KeyCode::ArrowUp)
-        {
-            Direction::Up
-        } else if keyboard_input.just_pressed(KeyCode::KeyA)
-            || keyboard_input.just_pressed(KeyCode::ArrowLeft)
-        {
-            Direction::Left
-        } else if keyboard_input.just_pressed(KeyCode::KeyS)
-            || keyboard_input.just_pressed(KeyCode::ArrowDown)
-        {
-            Direction::Down
-        } else if keyboard_input.just_pressed(KeyCode::KeyD)
-            || keyboard_input.just_pressed(KeyCode::ArrowRight)
-        {
-            Direction::Right
-        } else {
-            continue;
-        };
+    for (player, movable, mut queue_dir) in &mut query {
+        queue_dir.tick(time.delta());
+        steer(&actions, &rules, player.index, movable, &mut queue_dir);
+    }
+}
 
-        if new_dir == movable.direction {
-            log::info!("New dir is same as old dir, ignoring");
-            continue;
-        }
+/// Queues the direction [player] is steering in, the same way for Pac-Man and a ghost in versus
+/// mode.
+pub fn steer(
+    actions: &ActionState,
+    rules: &GameRules,
+    player: usize,
+    movable: &Movable,
+    queue_dir: &mut QueableDirection,
+) {
+    let just_pressed = Action::movement(player)
+        .iter()
+        .find(|(action, _)| actions.just_pressed(*action))
+        .map(|(_, dir)| dir);
 
-        // Check if the new dir will be legal.
-        if MAP.is_wall(&movable.target_tile.translate(&new_dir)) {
-            // Not a legal move.
-            continue;
+    if let Some(new_dir) = just_pressed {
+        if new_dir == &movable.direction {
+            // Changed our mind, keep going straight ahead.
+            queue_dir.clear();
+        } else {
+            queue_dir.queue(new_dir.clone(), &rules.input_buffer);
         }
+        return;
+    }
+
+    // Held directions are kept queued until they become legal or the key is released.
+    let held_dirs = Action::movement(player)
+        .iter()
+        .filter(|(action, dir)| actions.pressed(*action) && dir != &movable.direction)
+        .map(|(_, dir)| dir)
+        .collect::<Vec<_>>();
 
-        queue_dir.next_direction = Some(new_dir);
+    let held_dir = held_dirs
+        .iter()
+        .find(|dir| queue_dir.next_direction.as_ref() == Some(**dir))
+        .or(held_dirs.first());
+
+    if let Some(held_dir) = held_dir {
+        queue_dir.queue((*held_dir).clone(), &rules.input_buffer);
     }
 }
 
-pub fn player_take_move_decision(player: Single<(&Position, &mut Movable, &mut QueableDirection)>) {
-    let (position, mut movable, mut queued_dir) = player.into_inner();
+pub fn player_take_move_decision(
+    players: Query<(Entity, &Position, &mut Movable, &mut QueableDirection), With<Player>>,
+    rules: Res<GameRules>,
+    mut reach_target_events: EventReader<CharacterReachedTargetEvent>,
+) {
+    let passed_tile: Vec<Entity> = reach_target_events
+        .read()
+        .filter(|e| e.is_pacman())
+        .map(|e| e.entity)
+        .collect();
+
+    for (entity, position, movable, queued_dir) in players {
+        take_move_decision(
+            position,
+            movable.into_inner(),
+            queued_dir.into_inner(),
+            passed_tile.contains(&entity),
+            &rules,
+        );
+    }
+}
 
+fn take_move_decision(
+    position: &Position,
+    movable: &mut Movable,
+    queued_dir: &mut QueableDirection,
+    passed_tile: bool,
+    rules: &GameRules,
+) {
     let tile_pos: TilePos = position.0.clone().into();
     let has_reached_destination = tile_pos == movable.target_tile && position.in_middle_of_tile();
 
     if !has_reached_destination {
+        if rules.cornering > 0 {
+            try_corner(position, &tile_pos, movable, queued_dir, rules.cornering);
+        }
         return;
     }
 
-    // We've reached our destination
-    if let Some(queued_dir) = queued_dir.next_direction.take() {
-        movable.direction = queued_dir;
+    // We've reached our destination, take the queued direction if it's legal from here.
+    if let Some(next_dir) = queued_dir.next_direction.clone() {
+        if !MAP.is_wall(&tile_pos.translate(&next_dir)) {
+            movable.direction = next_dir;
+            queued_dir.clear();
+        } else if passed_tile {
+            queued_dir.tile_passed();
+        }
     }
 
     let new_target = tile_pos.translate(&movable.direction);
@@ -111,23 +176,145 @@ pub fn player_take_move_decision(player: Single<(&Position, &mut Movable, &mut Q
     movable.target_tile = new_target;
 }
 
-pub fn eat(
-    mut commands: Commands,
-    mut score: ResMut<Score>,
-    pacman: Single<(&Position, &mut Movable), With<Player>>,
-    food_query: Query<(&Position, &Scorable, Entity)>,
+/// Takes a queued perpendicular turn up to [window] pixels before reaching the middle of the
+/// tile (pre-turn) or after having passed it (post-turn), like in the arcade. The remaining
+/// distance to the centre of the lane is covered diagonally while moving.
+fn try_corner(
+    position: &Position,
+    tile_pos: &TilePos,
+    movable: &mut Movable,
+    queued_dir: &mut QueableDirection,
+    window: u32,
 ) {
-    let (pacman_position, mut movable) = pacman.into_inner();
+    let Some(next_dir) = queued_dir.next_direction.as_ref() else {
+        return;
+    };
+
+    if position.offset_along(&movable.direction).unsigned_abs() > window {
+        return;
+    }
 
-    if !pacman_position.in_middle_of_tile() {
+    if next_dir == &movable.direction || next_dir == &movable.direction.opposite() {
+        // Only perpendicular turns can be cornered.
         return;
     }
 
+    let new_target = tile_pos.translate(next_dir);
+    if MAP.is_wall(&new_target) {
+        return;
+    }
+
+    movable.direction = next_dir.clone();
+    movable.target_tile = new_target;
+    queued_dir.next_direction = None;
+}
+
+pub fn eat(
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    mut pacmen: Query<(&Position, &mut Movable), With<Player>>,
+    food_query: Query<(&Position, &Scorable, Entity)>,
+    mut eaten_events: EventWriter<ScorableEatenEvent>,
+) {
     for (position, scorable, entity) in food_query {
-        if position == pacman_position {
-            score.gain_score(scorable);
+        // Food is eaten when crossing the middle of its tile, which is off centre when
+        // Pac-Man cuts the corner.
+        let food_tile = TilePos::from(&position.0);
+        let eater = pacmen.iter_mut().find(|(pacman_position, movable)| {
+            TilePos::from(&pacman_position.0) == food_tile
+                && pacman_position.offset_along(&movable.direction) == 0
+        });
+
+        if let Some((_, mut movable)) = eater {
+            let points = score.gain_score(scorable);
             movable.pause(scorable);
             commands.entity(entity).despawn();
+
+            eaten_events.write(ScorableEatenEvent {
+                entity,
+                scorable: scorable.clone(),
+                position: position.0.clone(),
+                points,
+            });
         }
     }
 }
+
+pub fn eat_ghosts(
+    mut score: ResMut<Score>,
+    pacmen: Query<&Position, With<Player>>,
+    ghosts: Query<(Entity, &Position, &mut Ghost, &mut Movable), Without<Player>>,
+    mut eaten_events: EventWriter<ScorableEatenEvent>,
+) {
+    let pacman_tiles: Vec<TilePos> = pacmen.iter().map(|p| (&p.0).into()).collect();
+
+    for (entity, position, mut ghost, mut movable) in ghosts {
+        if ghost.current_mode != GhostMode::Frightened
+            || !pacman_tiles.contains(&TilePos::from(&position.0))
+        {
+            continue;
+        }
+
+        let points = score.gain_score(&Scorable::Ghost);
+        ghost.current_mode = GhostMode::Eaten;
+        movable.speed = ghost.current_mode.speed(ghost.base_speed);
+
+        eaten_events.write(ScorableEatenEvent {
+            entity,
+            scorable: Scorable::Ghost,
+            position: position.0.clone(),
+            points,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::InputBuffer;
+
+    /// Moving right along the top corridor towards the junction at (6, 1), which opens downwards,
+    /// [offset] pixels from its centre, wanting to go down.
+    fn approach_junction(offset: i32, window: u32) -> Movable {
+        let center: PixelPos = TilePos { x: 6, y: 1 }.into();
+        let position = Position(PixelPos {
+            x: center.x + offset,
+            y: center.y,
+        });
+        let tile_pos: TilePos = (&position.0).into();
+        let target = if offset > 0 {
+            tile_pos.translate(&Direction::Right)
+        } else {
+            tile_pos.clone()
+        };
+
+        let mut movable = Movable::new(target, Direction::Right, 0.8);
+        let mut queued_dir = QueableDirection::default();
+        queued_dir.queue(Direction::Down, &InputBuffer::Tiles(4));
+
+        try_corner(&position, &tile_pos, &mut movable, &mut queued_dir, window);
+        movable
+    }
+
+    #[test]
+    fn corners_just_inside_the_window() {
+        let pre_turn = approach_junction(-3, 3);
+        assert_eq!(pre_turn.direction, Direction::Down);
+        assert_eq!(pre_turn.target_tile, TilePos { x: 6, y: 2 });
+
+        let post_turn = approach_junction(3, 3);
+        assert_eq!(post_turn.direction, Direction::Down);
+        assert_eq!(post_turn.target_tile, TilePos { x: 6, y: 2 });
+    }
+
+    #[test]
+    fn keeps_going_just_outside_the_window() {
+        let too_early = approach_junction(-4, 3);
+        assert_eq!(too_early.direction, Direction::Right);
+        assert_eq!(too_early.target_tile, TilePos { x: 6, y: 1 });
+
+        let too_late = approach_junction(3, 2);
+        assert_eq!(too_late.direction, Direction::Right);
+        assert_eq!(too_late.target_tile, TilePos { x: 7, y: 1 });
+    }
+}
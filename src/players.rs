@@ -0,0 +1,207 @@
+use bevy::{log, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    animation::AnimationLibrary,
+    common::{CharacterSprites, TilePos},
+    components::{Player, Position},
+    fruit::DotsEaten,
+    level::Level,
+    lives::{Lives, STARTING_LIVES},
+    map::{MazeSprites, dot_tiles, spawn_dots},
+    player::pacman_bundle,
+    score::{Scorable, Score},
+    theme::Theme,
+};
+
+pub const MAX_PLAYERS: usize = 2;
+
+/// Everything a player keeps between turns.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerBoard {
+    pub score: u32,
+    pub lives: u32,
+    pub level: u32,
+    pub dots_eaten: u32,
+    /// The tiles of the dots and energizers left to eat.
+    remaining_dots: Vec<TilePos>,
+}
+
+impl Default for PlayerBoard {
+    fn default() -> Self {
+        Self {
+            score: 0,
+            lives: STARTING_LIVES,
+            level: Level::default().0,
+            dots_eaten: 0,
+            remaining_dots: dot_tiles(),
+        }
+    }
+}
+
+/// The players taking turns. The board of the player whose turn it is lives in the regular
+/// resources ([Score], [Lives], [Level], [DotsEaten]) and the [Scorable]s in the maze, the
+/// boards of the others are kept here until it's their turn.
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Players {
+    pub count: usize,
+    /// Two Pac-Men play at the same time, sharing a single board.
+    pub coop: bool,
+    /// The second player steers a ghost against Pac-Man.
+    pub versus: bool,
+    /// Index of the player whose turn it is.
+    pub current: usize,
+    boards: [PlayerBoard; MAX_PLAYERS],
+    /// Set when the turn passes to the next player, until their board is restored.
+    turn_over: bool,
+}
+
+impl Default for Players {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            coop: false,
+            versus: false,
+            current: 0,
+            boards: Default::default(),
+            turn_over: false,
+        }
+    }
+}
+
+impl Players {
+    /// Starts over with fresh boards, keeping how many are playing and how.
+    pub fn reset(&mut self) {
+        *self = Self {
+            count: self.count.clamp(1, MAX_PLAYERS),
+            coop: self.coop,
+            versus: self.versus,
+            ..default()
+        };
+    }
+
+    /// The score of [player], [score] being the score of the current player.
+    pub fn score_of(&self, player: usize, score: &Score) -> u32 {
+        if player == self.current {
+            score.score
+        } else {
+            self.boards[player].score
+        }
+    }
+
+    /// The next player with lives left, other than the current player.
+    pub fn next_player(&self) -> Option<usize> {
+        (1..self.count)
+            .map(|offset| (self.current + offset) % self.count)
+            .find(|&player| self.boards[player].lives > 0)
+    }
+
+    /// Passes the turn to the next player after losing a life, see [switch_player].
+    pub fn end_turn(&mut self) {
+        self.turn_over = true;
+    }
+
+    /// The best score (and the level it was reached on) of all players, for the high score table.
+    pub fn best_result(&self, score: &Score, level: &Level) -> (u32, u32) {
+        (0..self.count)
+            .map(|player| {
+                if player == self.current {
+                    (score.score, **level)
+                } else {
+                    (self.boards[player].score, self.boards[player].level)
+                }
+            })
+            .max_by_key(|(score, _)| *score)
+            .unwrap_or((score.score, **level))
+    }
+}
+
+/// Puts the board of the player whose turn ended away, and brings out the next player's.
+pub fn switch_player(
+    mut commands: Commands,
+    mut players: ResMut<Players>,
+    mut score: ResMut<Score>,
+    mut lives: ResMut<Lives>,
+    mut level: ResMut<Level>,
+    mut dots_eaten: ResMut<DotsEaten>,
+    scorables: Query<(Entity, &Position, &Scorable)>,
+) {
+    let Some(next) = players.next_player().filter(|_| players.turn_over) else {
+        return;
+    };
+
+    let mut remaining_dots = Vec::new();
+    for (entity, position, scorable) in &scorables {
+        if matches!(scorable, Scorable::Dot | Scorable::Energizer) {
+            remaining_dots.push((&position.0).into());
+        }
+        commands.entity(entity).despawn();
+    }
+
+    let current = players.current;
+    players.boards[current] = PlayerBoard {
+        score: score.score,
+        lives: **lives,
+        level: **level,
+        dots_eaten: **dots_eaten,
+        remaining_dots,
+    };
+
+    players.current = next;
+    let board = &players.boards[next];
+    *score = Score {
+        score: board.score,
+        ..Score::new()
+    };
+    **lives = board.lives;
+    **level = board.level;
+    **dots_eaten = board.dots_eaten;
+
+    log::info!("Player {}'s turn", next + 1);
+}
+
+/// Brings in the second Pac-Man for a co-op game, or takes it away otherwise.
+pub fn spawn_coop_pacman(
+    mut commands: Commands,
+    players: Res<Players>,
+    sprites: Res<CharacterSprites>,
+    animations: Res<AnimationLibrary>,
+    pacmen: Query<(Entity, &Player)>,
+) {
+    let mut has_second = false;
+    for (entity, player) in &pacmen {
+        if player.index == 0 {
+            continue;
+        }
+        if players.coop && !has_second {
+            has_second = true;
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    if players.coop && !has_second {
+        commands.spawn(pacman_bundle(
+            sprites.texture.clone(),
+            sprites.layout.clone(),
+            &animations,
+            1,
+        ));
+    }
+}
+
+/// Spawns the remaining dots of the player whose turn it now is.
+pub fn restore_player_dots(
+    mut commands: Commands,
+    mut players: ResMut<Players>,
+    sprites: Res<MazeSprites>,
+    theme: Res<Theme>,
+) {
+    if !players.turn_over {
+        return;
+    }
+
+    players.turn_over = false;
+    let board = &players.boards[players.current];
+    spawn_dots(&mut commands, &sprites, &theme, &board.remaining_dots);
+}
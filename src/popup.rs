@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+
+use crate::{
+    common::CharacterSprites, events::ScorableEatenEvent, map::TILE_SIZE, score::Scorable,
+};
+
+/// How long the action freezes while the points for an eaten ghost are shown.
+const GHOST_POPUP_SECONDS: f32 = 1.;
+const FRUIT_POPUP_SECONDS: f32 = 2.;
+
+/// The points scored for eating something, shown where it was eaten.
+#[derive(Component)]
+pub struct ScorePopup {
+    timer: Timer,
+    /// The eaten ghost, hidden while the popup is shown. The game is frozen until it reappears.
+    hidden: Option<Entity>,
+}
+
+fn ghost_points_sprite_index(points: u32) -> usize {
+    match points {
+        0..=200 => 112,
+        201..=400 => 113,
+        401..=800 => 114,
+        _ => 115,
+    }
+}
+
+/// Stops the action while the points for an eaten ghost are shown.
+pub fn run_if_not_frozen(popups: Query<&ScorePopup>) -> bool {
+    popups.iter().all(|popup| popup.hidden.is_none())
+}
+
+pub fn spawn_score_popups(
+    mut commands: Commands,
+    sprites: Res<CharacterSprites>,
+    mut eaten_events: EventReader<ScorableEatenEvent>,
+    mut visibilities: Query<&mut Visibility>,
+) {
+    for event in eaten_events.read() {
+        let (index, seconds, hidden) = match &event.scorable {
+            Scorable::Dot | Scorable::Energizer => continue,
+            Scorable::Ghost => (
+                ghost_points_sprite_index(event.points),
+                GHOST_POPUP_SECONDS,
+                Some(event.entity),
+            ),
+            Scorable::Fruit(fruit) => (fruit.points_sprite_index(), FRUIT_POPUP_SECONDS, None),
+        };
+
+        if let Some(mut visibility) = hidden.and_then(|e| visibilities.get_mut(e).ok()) {
+            *visibility = Visibility::Hidden;
+        }
+
+        // The popup is centred on the eaten entity, which is drawn from its top left corner.
+        let mut translation = event.position.to_character_display_pos();
+        translation.x += TILE_SIZE as f32;
+        translation.y -= TILE_SIZE as f32;
+        translation.z = 1.;
+
+        commands.spawn((
+            sprites.sprite(index),
+            ScorePopup {
+                timer: Timer::from_seconds(seconds, TimerMode::Once),
+                hidden,
+            },
+            Transform::from_translation(translation),
+        ));
+    }
+}
+
+pub fn update_score_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    popups: Query<(Entity, &mut ScorePopup)>,
+    mut visibilities: Query<&mut Visibility, Without<ScorePopup>>,
+) {
+    for (entity, mut popup) in popups {
+        popup.timer.tick(time.delta());
+        if !popup.timer.finished() {
+            continue;
+        }
+
+        if let Some(mut visibility) = popup.hidden.and_then(|e| visibilities.get_mut(e).ok()) {
+            *visibility = Visibility::Inherited;
+        }
+
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::{HashSet, VecDeque};
+
+use bevy::{prelude::*, ui::RelativeCursorPosition};
+
+use crate::{
+    common::TilePos,
+    debug::DebugRes,
+    pause::PauseRes,
+    players::Players,
+    snapshot::{FrameSnapshot, GameSnapshot, PendingSnapshot, capture_dots},
+    theme::Theme,
+};
+
+/// How many frames of play are kept to rewind through, ten seconds at 60 fps.
+const REWIND_FRAMES: usize = 600;
+const SCRUB_BAR_HEIGHT: f32 = 12.;
+
+/// A frame of play, with only what changed in the dots and the players since the frame before.
+struct RewindFrame {
+    state: FrameSnapshot,
+    /// The dots and energizers eaten since the frame before.
+    eaten: Vec<TilePos>,
+    /// The dots and energizers put back since the frame before, when a level starts.
+    spawned: Vec<TilePos>,
+    /// The players, when they changed since the frame before. Always there for the oldest frame.
+    players: Option<Players>,
+}
+
+/// The recent frames of play in debug mode, oldest first.
+#[derive(Resource, Default)]
+pub struct RewindBuffer {
+    frames: VecDeque<RewindFrame>,
+    /// The dots and energizers left in the newest frame.
+    dots: HashSet<TilePos>,
+    /// The players in the newest frame.
+    players: Players,
+    /// The frame shown while travelling back in time, play continues from here when unpaused.
+    cursor: Option<usize>,
+}
+
+impl RewindBuffer {
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.cursor = None;
+    }
+
+    fn current(&self) -> usize {
+        self.cursor
+            .unwrap_or_else(|| self.frames.len().saturating_sub(1))
+    }
+
+    /// Keeps a frame, only holding on to the dots and players that changed since the last one.
+    fn push(&mut self, state: FrameSnapshot, dots: HashSet<TilePos>, players: &Players) {
+        let first = self.frames.is_empty();
+        let frame = RewindFrame {
+            state,
+            eaten: self.dots.difference(&dots).cloned().collect(),
+            spawned: dots.difference(&self.dots).cloned().collect(),
+            players: (first || *players != self.players).then(|| players.clone()),
+        };
+
+        self.dots = dots;
+        if frame.players.is_some() {
+            self.players = players.clone();
+        }
+
+        self.frames.push_back(frame);
+        if self.frames.len() > REWIND_FRAMES
+            && let Some(oldest) = self.frames.pop_front()
+            && let Some(next) = self.frames.front_mut()
+        {
+            next.players = next.players.take().or(oldest.players);
+        }
+    }
+
+    /// Drops the frames after [index], to continue playing from there.
+    fn truncate(&mut self, index: usize) {
+        self.dots = self.dots_at(index);
+        self.players = self.players_at(index).clone();
+        self.frames.truncate(index + 1);
+    }
+
+    /// The dots and energizers left at the frame at [index], undoing the newer frames' changes.
+    fn dots_at(&self, index: usize) -> HashSet<TilePos> {
+        let mut dots = self.dots.clone();
+        for frame in self.frames.iter().skip(index + 1).rev() {
+            for tile in &frame.spawned {
+                dots.remove(tile);
+            }
+            dots.extend(frame.eaten.iter().cloned());
+        }
+
+        dots
+    }
+
+    /// The players at the frame at [index], as last changed at or before it.
+    fn players_at(&self, index: usize) -> &Players {
+        self.frames
+            .iter()
+            .take(index + 1)
+            .rev()
+            .find_map(|frame| frame.players.as_ref())
+            .unwrap_or(&self.players)
+    }
+
+    /// Shows the frame at [index], returning the snapshot to apply when it's a different one.
+    fn travel_to(&mut self, index: usize) -> Option<GameSnapshot> {
+        let index = index.min(self.frames.len().checked_sub(1)?);
+        if self.cursor == Some(index) {
+            return None;
+        }
+
+        self.cursor = Some(index);
+        Some(GameSnapshot {
+            frame: self.frames[index].state.clone(),
+            dots: self.dots_at(index).into_iter().collect(),
+            players: self.players_at(index).clone(),
+        })
+    }
+}
+
+#[derive(Component)]
+pub struct ScrubBar;
+
+/// The part of the [ScrubBar] up to the frame being shown.
+#[derive(Component)]
+pub struct ScrubBarFill;
+
+pub fn setup_scrub_bar(mut commands: Commands, theme: Res<Theme>) {
+    commands
+        .spawn((
+            ScrubBar,
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(SCRUB_BAR_HEIGHT),
+                left: Val::Percent(20.),
+                width: Val::Percent(60.),
+                height: Val::Px(SCRUB_BAR_HEIGHT),
+                ..default()
+            },
+            BackgroundColor(theme.colours.text().with_alpha(0.3)),
+            Interaction::default(),
+            RelativeCursorPosition::default(),
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ScrubBarFill,
+                Node {
+                    height: Val::Percent(100.),
+                    ..default()
+                },
+                BackgroundColor(theme.colours.highlight()),
+            ));
+        });
+}
+
+/// Keeps the frame that was just played, dropping the frames after the one travelled back to
+/// when play continues from there.
+pub fn record_frame(world: &mut World) {
+    let state = FrameSnapshot::capture(world);
+    let dots = capture_dots(world).into_iter().collect();
+    world.resource_scope(|world, mut buffer: Mut<RewindBuffer>| {
+        if let Some(cursor) = buffer.cursor.take() {
+            buffer.truncate(cursor);
+        }
+
+        buffer.push(state, dots, world.resource::<Players>());
+    });
+}
+
+/// Steps back a frame for every frame the rewind key is held, pausing the game.
+pub fn step_back(
+    mut commands: Commands,
+    mut buffer: ResMut<RewindBuffer>,
+    mut pause: ResMut<PauseRes>,
+) {
+    pause.paused = true;
+
+    let previous = buffer.current().saturating_sub(1);
+    if let Some(snapshot) = buffer.travel_to(previous) {
+        commands.insert_resource(PendingSnapshot(snapshot));
+    }
+}
+
+/// Jumps to the frame under the mouse while the scrub bar is held down.
+pub fn scrub(
+    mut commands: Commands,
+    bar: Single<(&Interaction, &RelativeCursorPosition, &Visibility), With<ScrubBar>>,
+    mut buffer: ResMut<RewindBuffer>,
+) {
+    let (interaction, cursor_position, visibility) = bar.into_inner();
+    if *interaction != Interaction::Pressed || visibility == Visibility::Hidden {
+        return;
+    }
+
+    let Some(position) = cursor_position.normalized else {
+        return;
+    };
+
+    let last = buffer.frames.len().saturating_sub(1);
+    let index = (position.x.clamp(0., 1.) * last as f32).round() as usize;
+    if let Some(snapshot) = buffer.travel_to(index) {
+        commands.insert_resource(PendingSnapshot(snapshot));
+    }
+}
+
+/// Shows the scrub bar while paused in debug mode, filled up to the frame being shown.
+pub fn update_scrub_bar(
+    debug: Res<DebugRes>,
+    pause: Res<PauseRes>,
+    buffer: Res<RewindBuffer>,
+    mut bar: Single<&mut Visibility, With<ScrubBar>>,
+    mut fill: Single<&mut Node, With<ScrubBarFill>>,
+) {
+    let shown = debug.debug_mode && pause.paused && buffer.frames.len() > 1;
+    **bar = if shown {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+
+    let last = buffer.frames.len().saturating_sub(1).max(1);
+    fill.width = Val::Percent(buffer.current() as f32 / last as f32 * 100.);
+}
@@ -0,0 +1,281 @@
+use std::{
+    env,
+    fmt::Display,
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{log, prelude::*};
+use serde::{Deserialize, Serialize};
+
+/// The first 8 KB of the arcade program ROM, which the arcade PRNG reads its numbers from.
+const ARCADE_ROM_PATHS: [&str; 2] = ["assets/roms/pacman.6e", "assets/roms/pacman.6f"];
+/// The arcade PRNG indexes the first 8 KB of the ROM.
+const ARCADE_ROM_SIZE: usize = 0x2000;
+const ARCADE_INDEX_MASK: u32 = ARCADE_ROM_SIZE as u32 - 1;
+
+/// Which generator produces the random numbers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RngMode {
+    Xorshift,
+    /// Copies the arcade, stepping an index with `index * 5 + 1` (13 bits) and reading the byte
+    /// at that address of the program ROM. It restarts from the seed every life and level like
+    /// the arcade does, so the frightened ghosts turn the same way every time. Falls back to
+    /// [RngMode::Xorshift] without the ROM files in `assets/roms`.
+    Arcade,
+}
+
+impl Display for RngMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Random numbers for gameplay decisions, like which way a frightened ghost turns.
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u32,
+    mode: RngMode,
+    state: u32,
+    /// The arcade program ROM, only loaded in arcade mode.
+    rom: Vec<u8>,
+}
+
+impl GameRng {
+    /// Falls back to [RngMode::Xorshift] when the arcade mode is asked for without the ROM.
+    pub fn new(seed: u32, mode: RngMode) -> Self {
+        let (mode, rom) = match mode {
+            RngMode::Arcade => match load_arcade_rom() {
+                Some(rom) => (RngMode::Arcade, rom),
+                None => {
+                    log::warn!("Using xorshift random numbers instead, without the arcade ROM");
+                    (RngMode::Xorshift, Vec::new())
+                }
+            },
+            RngMode::Xorshift => (RngMode::Xorshift, Vec::new()),
+        };
+
+        let mut rng = Self {
+            seed,
+            mode,
+            state: 0,
+            rom,
+        };
+        rng.rewind();
+        rng
+    }
+
+    pub fn from_seed(seed: u32) -> Self {
+        Self::new(seed, RngMode::Xorshift)
+    }
+
+    /// Uses the seed given with `--seed <number>` on the command line (a random one otherwise),
+    /// and the arcade generator with `--arcade-rng`.
+    pub fn from_args() -> Self {
+        let seed = env::args().skip_while(|arg| arg != "--seed").nth(1);
+        let seed = match seed {
+            Some(seed) => seed.parse().unwrap_or_else(|err| {
+                log::error!("Invalid seed {seed} ({err}), using a random one");
+                random_seed()
+            }),
+            None => random_seed(),
+        };
+
+        let mode = if env::args().any(|arg| arg == "--arcade-rng") {
+            RngMode::Arcade
+        } else {
+            RngMode::Xorshift
+        };
+
+        let rng = Self::new(seed, mode);
+        log::info!("Using {} random numbers with seed {seed}", rng.mode);
+        rng
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    pub fn mode(&self) -> &RngMode {
+        &self.mode
+    }
+
+    /// The generator's position in its sequence, restore it with [GameRng::set_state].
+    pub fn state(&self) -> u32 {
+        self.state
+    }
+
+    pub fn set_state(&mut self, state: u32) {
+        self.state = state;
+    }
+
+    /// Goes back to the start of the sequence, for a new game to play out the same way.
+    pub fn rewind(&mut self) {
+        self.state = match self.mode {
+            // Xorshift gets stuck on a zero state.
+            RngMode::Xorshift => self.seed.max(1),
+            RngMode::Arcade => self.seed & ARCADE_INDEX_MASK,
+        };
+    }
+
+    /// The arcade goes back to the start of the sequence at every life and level, the xorshift
+    /// sequence carries on through the game.
+    pub fn restart_life(&mut self) {
+        if self.mode == RngMode::Arcade {
+            self.rewind();
+        }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        match self.mode {
+            RngMode::Xorshift => {
+                let mut x = self.state;
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                self.state = x;
+                x
+            }
+            RngMode::Arcade => {
+                self.state = (self.state * 5 + 1) & ARCADE_INDEX_MASK;
+                self.rom[self.state as usize] as u32
+            }
+        }
+    }
+
+    /// Returns a random number in 0..[max].
+    pub fn below(&mut self, max: u32) -> u32 {
+        self.next_u32() % max.max(1)
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_seed(random_seed())
+    }
+}
+
+fn random_seed() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default()
+}
+
+fn load_arcade_rom() -> Option<Vec<u8>> {
+    let mut rom = Vec::new();
+    for path in ARCADE_ROM_PATHS {
+        match fs::read(path) {
+            Ok(contents) => rom.extend(contents),
+            Err(err) => {
+                log::warn!("Failed to read arcade ROM {path} ({err})");
+                return None;
+            }
+        }
+    }
+
+    if rom.len() < ARCADE_ROM_SIZE {
+        log::warn!("The arcade ROM is only {} bytes", rom.len());
+        return None;
+    }
+
+    Some(rom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An arcade generator reading [rom], without looking for the ROM files.
+    fn arcade(seed: u32, rom: Vec<u8>) -> GameRng {
+        let mut rng = GameRng {
+            seed,
+            mode: RngMode::Arcade,
+            state: 0,
+            rom,
+        };
+        rng.rewind();
+        rng
+    }
+
+    #[test]
+    fn arcade_steps_the_index_by_five_plus_one() {
+        // Every byte of this ROM is the low byte of its address.
+        let rom = (0..ARCADE_ROM_SIZE).map(|i| i as u8).collect();
+        let mut rng = arcade(0, rom);
+        let numbers = (0..6).map(|_| rng.next_u32()).collect::<Vec<_>>();
+
+        assert_eq!(numbers, [1, 6, 31, 156, 781 & 0xFF, 3906 & 0xFF]);
+        assert_eq!(rng.state(), 3906);
+    }
+
+    #[test]
+    fn arcade_reads_the_rom_at_the_index() {
+        let rom = (0..ARCADE_ROM_SIZE)
+            .map(|i| (i / 3) as u8)
+            .collect::<Vec<_>>();
+        let mut rng = arcade(42, rom.clone());
+
+        for index in [211, 1056, 5281] {
+            assert_eq!(rng.next_u32(), rom[index] as u32);
+        }
+    }
+
+    #[test]
+    fn arcade_index_visits_all_of_the_rom_before_repeating() {
+        let mut rng = arcade(0, vec![0; ARCADE_ROM_SIZE]);
+        let mut seen = vec![false; ARCADE_ROM_SIZE];
+
+        for _ in 0..ARCADE_ROM_SIZE {
+            rng.next_u32();
+            seen[rng.state() as usize] = true;
+        }
+
+        assert!(seen.iter().all(|&seen| seen));
+        assert_eq!(rng.state(), 0);
+    }
+
+    #[test]
+    fn arcade_without_the_rom_falls_back_to_xorshift() {
+        let rng = GameRng::new(7, RngMode::Arcade);
+        let expected = match load_arcade_rom() {
+            Some(_) => RngMode::Arcade,
+            None => RngMode::Xorshift,
+        };
+
+        assert_eq!(rng.mode(), &expected);
+    }
+
+    #[test]
+    fn only_the_arcade_restarts_every_life() {
+        let mut rng = arcade(7, (0..ARCADE_ROM_SIZE).map(|i| i as u8).collect());
+        let first = rng.next_u32();
+        rng.next_u32();
+        rng.restart_life();
+        assert_eq!(rng.next_u32(), first);
+
+        let mut rng = GameRng::from_seed(7);
+        let first = rng.next_u32();
+        rng.restart_life();
+        assert_ne!(rng.next_u32(), first);
+    }
+
+    #[test]
+    fn the_same_seed_plays_out_the_same() {
+        let mut a = GameRng::from_seed(1234);
+        let mut b = GameRng::from_seed(1234);
+        for _ in 0..100 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+
+        a.rewind();
+        b = GameRng::from_seed(1234);
+        assert_eq!(a.next_u32(), b.next_u32());
+    }
+
+    #[test]
+    fn xorshift_does_not_get_stuck_on_a_zero_seed() {
+        let mut rng = GameRng::from_seed(0);
+        assert_ne!(rng.next_u32(), 0);
+    }
+}
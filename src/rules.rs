@@ -0,0 +1,216 @@
+use std::{env, fmt::Display, str::FromStr};
+
+use bevy::{log, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{Direction, TilePos, load_ron_or},
+    ghosts::GhostName,
+    score::ScoreThresholds,
+};
+
+pub const RULES_PATH: &str = "assets/config/rules.ron";
+
+/// How faithfully ghost targeting copies the original arcade code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TargetingAccuracy {
+    /// Copies the arcade overflow bug, looking ahead of an upwards facing Pac-Man
+    /// also moves the target to the left by the same amount.
+    Arcade,
+    /// Always looks straight ahead of Pac-Man.
+    Corrected,
+}
+
+impl TargetingAccuracy {
+    /// Returns the tile `tiles` tiles ahead of `pos` when facing `dir`.
+    pub fn tiles_ahead(&self, pos: &TilePos, dir: &Direction, tiles: i32) -> TilePos {
+        match (self, dir) {
+            (TargetingAccuracy::Arcade, Direction::Up) => TilePos {
+                x: pos.x - tiles,
+                y: pos.y - tiles,
+            },
+            (_, Direction::Up) => TilePos {
+                x: pos.x,
+                y: pos.y - tiles,
+            },
+            (_, Direction::Right) => TilePos {
+                x: pos.x + tiles,
+                y: pos.y,
+            },
+            (_, Direction::Down) => TilePos {
+                x: pos.x,
+                y: pos.y + tiles,
+            },
+            (_, Direction::Left) => TilePos {
+                x: pos.x - tiles,
+                y: pos.y,
+            },
+        }
+    }
+}
+
+impl Display for TargetingAccuracy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Which Pac-Man a ghost chases when two are playing together.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CoopTargeting {
+    /// Every ghost chases the Pac-Man closest to it.
+    Nearest,
+    /// Blinky and Inky chase player one, Pinky and Clyde player two.
+    Split,
+}
+
+impl Display for CoopTargeting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Which game the ghosts (and the bonus fruit) behave like.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RulesProfile {
+    PacMan,
+    /// Blinky and Pinky turn at random during the first scatter period of a level, and the
+    /// bonus fruit wanders through the maze instead of sitting below the ghost house.
+    MsPacMan,
+}
+
+impl RulesProfile {
+    /// Whether [ghost] turns at random instead of heading for its corner in the first scatter.
+    pub fn wanders_in_first_scatter(&self, ghost: &GhostName) -> bool {
+        *self == RulesProfile::MsPacMan && matches!(ghost, GhostName::Blinky | GhostName::Pinky)
+    }
+
+    pub fn wandering_fruit(&self) -> bool {
+        *self == RulesProfile::MsPacMan
+    }
+}
+
+impl FromStr for RulesProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pacman" => Ok(RulesProfile::PacMan),
+            "mspacman" => Ok(RulesProfile::MsPacMan),
+            _ => Err(format!(
+                "Unknown rules profile '{s}', use pacman or mspacman"
+            )),
+        }
+    }
+}
+
+impl Display for RulesProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// How long a direction pressed ahead of time is remembered while waiting for the turn to become legal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InputBuffer {
+    /// Remember the direction while passing through this many tiles.
+    Tiles(u32),
+    /// Remember the direction for this many milliseconds.
+    Millis(u32),
+}
+
+impl Display for InputBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputBuffer::Tiles(tiles) => write!(f, "{tiles} tiles"),
+            InputBuffer::Millis(millis) => write!(f, "{millis} ms"),
+        }
+    }
+}
+
+/// Options missing from the rules file keep their default.
+#[derive(Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameRules {
+    pub profile: RulesProfile,
+    pub targeting: TargetingAccuracy,
+    /// How many pixels before or after the middle of a tile Pac-Man may turn to cut a corner,
+    /// 0 only allows turns exactly at the tile centre.
+    pub cornering: u32,
+    pub input_buffer: InputBuffer,
+    /// The scores at which an extra life is awarded.
+    pub extra_life: ScoreThresholds,
+    pub coop_targeting: CoopTargeting,
+    /// The ghost the second player steers in versus mode.
+    pub versus_ghost: GhostName,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            profile: RulesProfile::PacMan,
+            targeting: TargetingAccuracy::Arcade,
+            cornering: 3,
+            input_buffer: InputBuffer::Tiles(4),
+            extra_life: ScoreThresholds::List(vec![10_000]),
+            coop_targeting: CoopTargeting::Nearest,
+            versus_ghost: GhostName::Clyde,
+        }
+    }
+}
+
+impl GameRules {
+    /// Loads the rules from [path], falling back to the default rules if it can't be read.
+    pub fn load_or_default(path: &str) -> Self {
+        load_ron_or(path, "game rules", Self::default)
+    }
+
+    /// Loads the rules from [RULES_PATH], with the profile given with
+    /// `--profile <pacman|mspacman>` on the command line taking over the one in the file.
+    pub fn from_args() -> Self {
+        let mut rules = Self::load_or_default(RULES_PATH);
+
+        let mut args = env::args().skip_while(|arg| arg != "--profile").skip(1);
+        if let Some(profile) = args.next() {
+            match profile.parse() {
+                Ok(profile) => rules.profile = profile,
+                Err(err) => log::error!("{err}, keeping {}", rules.profile),
+            }
+        }
+
+        log::info!("Using the {} rules", rules.profile);
+        rules
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn profile_is_parsed_from_the_command_line_name() {
+        assert_eq!("pacman".parse(), Ok(RulesProfile::PacMan));
+        assert_eq!("MsPacMan".parse(), Ok(RulesProfile::MsPacMan));
+        assert!("galaxian".parse::<RulesProfile>().is_err());
+    }
+
+    #[test]
+    fn ms_pacman_profile_enables_wandering() {
+        let profile = RulesProfile::MsPacMan;
+        assert!(profile.wandering_fruit());
+        assert!(profile.wanders_in_first_scatter(&GhostName::Blinky));
+        assert!(profile.wanders_in_first_scatter(&GhostName::Pinky));
+        assert!(!profile.wanders_in_first_scatter(&GhostName::Inky));
+
+        assert!(!RulesProfile::PacMan.wandering_fruit());
+        assert!(!RulesProfile::PacMan.wanders_in_first_scatter(&GhostName::Blinky));
+    }
+
+    #[test]
+    fn checked_in_rules_file_parses() {
+        let contents = fs::read_to_string(RULES_PATH).unwrap();
+        assert!(ron::from_str::<GameRules>(&contents).is_ok());
+    }
+}
@@ -1,6 +1,11 @@
+use std::collections::HashMap;
+
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Resource)]
+use crate::{events::ScoreMilestoneEvent, fruit::Fruit, players::Players, rules::GameRules};
+
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
 pub struct Score {
     pub score: u32,
     pub ghosts_eaten: u32,
@@ -14,22 +19,30 @@ impl Score {
         }
     }
 
-    pub fn gain_score(&mut self, scorable: &Scorable) {
+    /// Adds the points for [scorable] to the score, returning the amount of points gained.
+    pub fn gain_score(&mut self, scorable: &Scorable) -> u32 {
         let score = match scorable {
             Scorable::Dot => 10,
             Scorable::Energizer => 50,
-            Scorable::Ghost => 200 * 2u32.pow(self.ghosts_eaten),
+            Scorable::Ghost => {
+                let score = 200 * 2u32.pow(self.ghosts_eaten);
+                self.ghosts_eaten += 1;
+                score
+            }
+            Scorable::Fruit(fruit) => fruit.points(),
         };
 
         self.score += score;
+        score
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
 pub enum Scorable {
     Dot,
     Energizer,
     Ghost,
+    Fruit(Fruit),
 }
 
 impl Scorable {
@@ -37,7 +50,139 @@ impl Scorable {
         Some(match self {
             Scorable::Dot => 1,
             Scorable::Energizer => 3,
-            Scorable::Ghost => return None,
+            Scorable::Ghost | Scorable::Fruit(_) => return None,
         })
     }
 }
+
+/// A set of scores, either listed explicitly or repeating at a fixed interval.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScoreThresholds {
+    List(Vec<u32>),
+    /// With `every` at 0 only `first` counts.
+    Repeating {
+        first: u32,
+        every: u32,
+    },
+}
+
+impl ScoreThresholds {
+    /// Returns the thresholds passed when the score went from [previous] to [current].
+    pub fn crossed(&self, previous: u32, current: u32) -> Vec<u32> {
+        match self {
+            ScoreThresholds::List(thresholds) => thresholds
+                .iter()
+                .copied()
+                .filter(|t| previous < *t && *t <= current)
+                .collect(),
+            ScoreThresholds::Repeating { first, every: 0 } => {
+                ScoreThresholds::List(vec![*first]).crossed(previous, current)
+            }
+            ScoreThresholds::Repeating { first, every } => {
+                // The amount of thresholds at or below the score.
+                let reached = |score: u32| {
+                    if score < *first {
+                        0
+                    } else {
+                        (score - first) / *every + 1
+                    }
+                };
+
+                (reached(previous)..reached(current))
+                    .map(|n| first + n * every)
+                    .collect()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Milestone {
+    ExtraLife,
+}
+
+/// The score milestones to send [ScoreMilestoneEvent]s for, systems subscribe by adding their own.
+#[derive(Resource)]
+pub struct ScoreMilestones(pub HashMap<Milestone, ScoreThresholds>);
+
+impl FromWorld for ScoreMilestones {
+    fn from_world(world: &mut World) -> Self {
+        let rules = world.resource::<GameRules>();
+        Self(HashMap::from([(
+            Milestone::ExtraLife,
+            rules.extra_life.clone(),
+        )]))
+    }
+}
+
+pub fn check_score_milestones(
+    score: Res<Score>,
+    players: Res<Players>,
+    milestones: Res<ScoreMilestones>,
+    mut previous_score: Local<u32>,
+    mut milestone_events: EventWriter<ScoreMilestoneEvent>,
+) {
+    if score.score < *previous_score || players.is_changed() {
+        // The score was reset for a new game, or it's another player's turn.
+        *previous_score = score.score;
+    }
+
+    if score.score == *previous_score {
+        return;
+    }
+
+    for (milestone, thresholds) in milestones.0.iter() {
+        for threshold in thresholds.crossed(*previous_score, score.score) {
+            milestone_events.write(ScoreMilestoneEvent {
+                milestone: milestone.clone(),
+                threshold,
+            });
+        }
+    }
+
+    *previous_score = score.score;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_thresholds_crossed() {
+        let thresholds = ScoreThresholds::List(vec![10_000, 20_000, 50_000]);
+
+        assert_eq!(thresholds.crossed(9_990, 10_000), [10_000]);
+        assert_eq!(thresholds.crossed(10_000, 10_010), [] as [u32; 0]);
+        assert_eq!(thresholds.crossed(5_000, 60_000), [10_000, 20_000, 50_000]);
+        assert_eq!(thresholds.crossed(12_000, 12_000), [] as [u32; 0]);
+    }
+
+    #[test]
+    fn repeating_thresholds_crossed() {
+        let thresholds = ScoreThresholds::Repeating {
+            first: 10_000,
+            every: 20_000,
+        };
+
+        assert_eq!(thresholds.crossed(0, 9_990), [] as [u32; 0]);
+        assert_eq!(thresholds.crossed(9_990, 10_000), [10_000]);
+        assert_eq!(thresholds.crossed(10_000, 29_990), [] as [u32; 0]);
+        assert_eq!(thresholds.crossed(29_990, 30_000), [30_000]);
+        assert_eq!(
+            thresholds.crossed(0, 75_000),
+            [10_000, 30_000, 50_000, 70_000]
+        );
+        assert_eq!(thresholds.crossed(30_000, 30_000), [] as [u32; 0]);
+    }
+
+    #[test]
+    fn repeating_every_zero_only_counts_the_first() {
+        let thresholds = ScoreThresholds::Repeating {
+            first: 100,
+            every: 0,
+        };
+
+        assert_eq!(thresholds.crossed(98, 5_000), [100]);
+        assert_eq!(thresholds.crossed(100, 5_000), [] as [u32; 0]);
+    }
+}
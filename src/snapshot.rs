@@ -0,0 +1,401 @@
+use std::{collections::HashSet, fs};
+
+use bevy::{log, prelude::*, sprite::Anchor};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    attract::Demo,
+    common::{Character, CharacterSprites, TilePos},
+    components::{Ghost, GhostTarget, Movable, Player, Position, QueableDirection},
+    fruit::{BonusFruit, DotsEaten, Fruit, WanderingFruit},
+    game_state::GameState,
+    ghosts::{
+        GhostName,
+        ghost_mode::{FrightenedRes, GhostMode, GhostModeRes},
+    },
+    level::Level,
+    lives::Lives,
+    map::{MazeSprites, spawn_dots},
+    players::Players,
+    rng::{GameRng, RngMode},
+    score::{Scorable, Score},
+    theme::Theme,
+};
+
+pub const SAVE_GAME_PATH: &str = "savegame.ron";
+
+/// Which character a [CharacterRecord] belongs to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum CharacterKey {
+    /// The Pac-Man steered by this player.
+    Pacman(usize),
+    Ghost(GhostName),
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct CharacterRecord {
+    key: CharacterKey,
+    position: Position,
+    movable: Movable,
+    queued_dir: Option<QueableDirection>,
+    /// The mode and target of a ghost.
+    ghost: Option<(GhostMode, GhostTarget)>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct FruitRecord {
+    fruit: Fruit,
+    position: Position,
+    timer: Timer,
+    /// The movement and the waypoints left of a wandering fruit.
+    wandering: Option<(Movable, Vec<TilePos>)>,
+}
+
+/// What changes from one frame of play to the next, the dots and the players aside.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FrameSnapshot {
+    characters: Vec<CharacterRecord>,
+    fruit: Option<FruitRecord>,
+    score: Score,
+    lives: u32,
+    level: u32,
+    dots_eaten: u32,
+    ghost_mode: GhostModeRes,
+    frightened: Option<Timer>,
+    rng_seed: u32,
+    rng_mode: RngMode,
+    rng_state: u32,
+}
+
+/// Everything needed to pick a game up again exactly where it was.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    pub frame: FrameSnapshot,
+    /// The tiles of the dots and energizers left to eat.
+    pub dots: Vec<TilePos>,
+    /// Who's playing, with the boards of the players waiting for their turn.
+    pub players: Players,
+}
+
+impl GameSnapshot {
+    pub fn capture(world: &mut World) -> Self {
+        Self {
+            frame: FrameSnapshot::capture(world),
+            dots: capture_dots(world),
+            players: world.resource::<Players>().clone(),
+        }
+    }
+
+    /// Puts the game back the way it was captured.
+    pub fn apply(&self, world: &mut World) {
+        *world.resource_mut::<Players>() = self.players.clone();
+        self.frame.apply(world);
+        restore_dots(world, &self.dots);
+    }
+
+    pub fn save(&self, path: &str) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(err) => {
+                log::error!("Failed to serialize the game ({err})");
+                return;
+            }
+        };
+
+        match fs::write(path, contents) {
+            Ok(()) => log::info!("Saved the game to {path}"),
+            Err(err) => log::error!("Failed to save the game to {path} ({err})"),
+        }
+    }
+
+    pub fn load(path: &str) -> Option<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                log::warn!("Failed to read a saved game from {path} ({err})");
+                return None;
+            }
+        };
+
+        match ron::from_str(&contents) {
+            Ok(snapshot) => Some(snapshot),
+            Err(err) => {
+                log::error!("Failed to parse the saved game in {path} ({err})");
+                None
+            }
+        }
+    }
+}
+
+impl FrameSnapshot {
+    pub fn capture(world: &mut World) -> Self {
+        let mut characters = world.query::<(
+            Option<&Player>,
+            Option<&Ghost>,
+            &Position,
+            &Movable,
+            Option<&QueableDirection>,
+            Option<&GhostTarget>,
+        )>();
+        let characters = characters
+            .iter(world)
+            .filter_map(|(player, ghost, position, movable, queued_dir, target)| {
+                let key = match (player, ghost) {
+                    (Some(player), _) => CharacterKey::Pacman(player.index),
+                    (_, Some(ghost)) => CharacterKey::Ghost(ghost.ghost.clone()),
+                    // The wandering fruit is kept with the other fruit details.
+                    _ => return None,
+                };
+
+                Some(CharacterRecord {
+                    key,
+                    position: position.clone(),
+                    movable: movable.clone(),
+                    queued_dir: queued_dir.cloned(),
+                    ghost: ghost
+                        .zip(target)
+                        .map(|(ghost, target)| (ghost.current_mode.clone(), target.clone())),
+                })
+            })
+            .collect();
+
+        let mut fruits = world.query::<(
+            &Position,
+            &Scorable,
+            &BonusFruit,
+            Option<&Movable>,
+            Option<&WanderingFruit>,
+        )>();
+        let fruit =
+            fruits
+                .iter(world)
+                .find_map(|(position, scorable, bonus, movable, wandering)| {
+                    let Scorable::Fruit(fruit) = scorable else {
+                        return None;
+                    };
+
+                    Some(FruitRecord {
+                        fruit: fruit.clone(),
+                        position: position.clone(),
+                        timer: bonus.timer.clone(),
+                        wandering: movable.zip(wandering).map(|(movable, wandering)| {
+                            (movable.clone(), wandering.waypoints.clone())
+                        }),
+                    })
+                });
+
+        let rng = world.resource::<GameRng>();
+
+        Self {
+            characters,
+            fruit,
+            score: world.resource::<Score>().clone(),
+            lives: **world.resource::<Lives>(),
+            level: **world.resource::<Level>(),
+            dots_eaten: **world.resource::<DotsEaten>(),
+            ghost_mode: world.resource::<GhostModeRes>().clone(),
+            frightened: world.resource::<FrightenedRes>().timer.clone(),
+            rng_seed: rng.seed(),
+            rng_mode: rng.mode().clone(),
+            rng_state: rng.state(),
+        }
+    }
+
+    /// Puts the frame back the way it was captured.
+    pub fn apply(&self, world: &mut World) {
+        *world.resource_mut::<Score>() = self.score.clone();
+        **world.resource_mut::<Lives>() = self.lives;
+        **world.resource_mut::<Level>() = self.level;
+        **world.resource_mut::<DotsEaten>() = self.dots_eaten;
+        *world.resource_mut::<GhostModeRes>() = self.ghost_mode.clone();
+        world.resource_mut::<FrightenedRes>().timer = self.frightened.clone();
+
+        let mut rng = world.resource_mut::<GameRng>();
+        if rng.seed() != self.rng_seed || rng.mode() != &self.rng_mode {
+            *rng = GameRng::new(self.rng_seed, self.rng_mode.clone());
+        }
+        rng.set_state(self.rng_state);
+
+        self.apply_characters(world);
+        self.respawn_fruit(world);
+    }
+
+    fn apply_characters(&self, world: &mut World) {
+        let mut characters = world.query::<(
+            Option<&Player>,
+            Option<&mut Ghost>,
+            &mut Position,
+            &mut Movable,
+            Option<&mut QueableDirection>,
+            Option<&mut GhostTarget>,
+        )>();
+
+        for (player, ghost, mut position, mut movable, queued_dir, target) in
+            characters.iter_mut(world)
+        {
+            let key = match (player, &ghost) {
+                (Some(player), _) => CharacterKey::Pacman(player.index),
+                (_, Some(ghost)) => CharacterKey::Ghost(ghost.ghost.clone()),
+                _ => continue,
+            };
+
+            let Some(record) = self.characters.iter().find(|r| r.key == key) else {
+                log::warn!("The snapshot has no {key:?}, leaving it where it is");
+                continue;
+            };
+
+            *position = record.position.clone();
+            *movable = record.movable.clone();
+
+            if let (Some(mut queued_dir), Some(record)) = (queued_dir, &record.queued_dir) {
+                *queued_dir = record.clone();
+            }
+
+            if let (Some(mut ghost), Some(mut target), Some((mode, record))) =
+                (ghost, target, &record.ghost)
+            {
+                ghost.current_mode = mode.clone();
+                *target = record.clone();
+            }
+        }
+    }
+
+    /// Swaps the fruit in the maze for the captured one.
+    fn respawn_fruit(&self, world: &mut World) {
+        let mut fruits = world.query_filtered::<Entity, With<BonusFruit>>();
+        let stale = fruits.iter(world).collect::<Vec<_>>();
+
+        let character_sprites = world.resource::<CharacterSprites>().clone();
+
+        let mut commands = world.commands();
+        for entity in stale {
+            commands.entity(entity).despawn();
+        }
+
+        if let Some(record) = &self.fruit {
+            let mut sprite = character_sprites.sprite(record.fruit.sprite_index());
+            sprite.anchor = Anchor::TopLeft;
+
+            let mut fruit = commands.spawn((
+                sprite,
+                BonusFruit {
+                    timer: record.timer.clone(),
+                },
+                Transform::from_translation(record.position.to_character_display_pos()),
+                record.position.clone(),
+                Scorable::Fruit(record.fruit.clone()),
+            ));
+
+            if let Some((movable, waypoints)) = &record.wandering {
+                fruit.insert((
+                    WanderingFruit {
+                        waypoints: waypoints.clone(),
+                    },
+                    Character::Fruit,
+                    movable.clone(),
+                ));
+            }
+        }
+
+        world.flush();
+    }
+}
+
+/// The tiles of the dots and energizers left to eat.
+pub fn capture_dots(world: &mut World) -> Vec<TilePos> {
+    let mut scorables = world.query::<(&Position, &Scorable)>();
+    scorables
+        .iter(world)
+        .filter(|(_, scorable)| matches!(scorable, Scorable::Dot | Scorable::Energizer))
+        .map(|(position, _)| (&position.0).into())
+        .collect()
+}
+
+/// Eats or puts back only the dots and energizers that differ from [dots].
+fn restore_dots(world: &mut World, dots: &[TilePos]) {
+    let mut missing = dots.iter().cloned().collect::<HashSet<_>>();
+    let mut scorables = world.query::<(Entity, &Position, &Scorable)>();
+    let eaten = scorables
+        .iter(world)
+        .filter(|(_, position, scorable)| {
+            matches!(scorable, Scorable::Dot | Scorable::Energizer)
+                && !missing.remove(&(&position.0).into())
+        })
+        .map(|(entity, _, _)| entity)
+        .collect::<Vec<_>>();
+    let missing = missing.into_iter().collect::<Vec<_>>();
+
+    let maze_sprites = world.resource::<MazeSprites>().clone();
+    let theme = world.resource::<Theme>().clone();
+
+    let mut commands = world.commands();
+    for entity in eaten {
+        commands.entity(entity).despawn();
+    }
+
+    spawn_dots(&mut commands, &maze_sprites, &theme, &missing);
+    world.flush();
+}
+
+/// A snapshot waiting to be applied once the game is playing.
+#[derive(Resource)]
+pub struct PendingSnapshot(pub GameSnapshot);
+
+/// The debug save slot, for playing a tricky situation over and over.
+#[derive(Resource, Default)]
+pub struct SnapshotSlot(Option<GameSnapshot>);
+
+pub fn save_game(world: &mut World) {
+    if world.contains_resource::<Demo>() {
+        return;
+    }
+
+    GameSnapshot::capture(world).save(SAVE_GAME_PATH);
+}
+
+/// Resumes the saved game, starting a game first when on the title screen.
+pub fn load_game(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    mut players: ResMut<Players>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(snapshot) = GameSnapshot::load(SAVE_GAME_PATH) else {
+        return;
+    };
+
+    log::info!("Resuming the game saved in {SAVE_GAME_PATH}");
+    if *state.get() == GameState::Title {
+        // The rest of the players is restored with the snapshot, once the game has started.
+        players.count = snapshot.players.count;
+        players.coop = snapshot.players.coop;
+        players.versus = snapshot.players.versus;
+        next_state.set(GameState::Playing);
+    }
+
+    commands.insert_resource(PendingSnapshot(snapshot));
+}
+
+pub fn save_slot(world: &mut World) {
+    let snapshot = GameSnapshot::capture(world);
+    world.resource_mut::<SnapshotSlot>().0 = Some(snapshot);
+    log::info!("Saved the debug slot");
+}
+
+pub fn load_slot(mut commands: Commands, slot: Res<SnapshotSlot>) {
+    let Some(snapshot) = slot.0.clone() else {
+        log::warn!("The debug slot is empty");
+        return;
+    };
+
+    log::info!("Going back to the debug slot");
+    commands.insert_resource(PendingSnapshot(snapshot));
+}
+
+pub fn apply_pending_snapshot(world: &mut World) {
+    let Some(PendingSnapshot(snapshot)) = world.remove_resource::<PendingSnapshot>() else {
+        return;
+    };
+
+    snapshot.apply(world);
+}
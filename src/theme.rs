@@ -0,0 +1,99 @@
+use std::{collections::HashMap, env};
+
+use bevy::{log, prelude::*};
+use serde::Deserialize;
+
+use crate::{animation::AtlasGrid, common::load_ron_or, map::WallType};
+
+pub const THEMES_DIR: &str = "assets/themes";
+pub const DEFAULT_THEME: &str = "arcade";
+/// Used when the theme can't be loaded.
+const DEFAULT_THEME_RON: &str = include_str!("../assets/themes/arcade.ron");
+
+/// Colours as hex RGB(A), like "#ffff00".
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThemeColours {
+    pub background: String,
+    /// Regular text, like the HUD.
+    pub text: String,
+    /// Text that should stand out, like a new high score.
+    pub highlight: String,
+    /// Bad news, like game over.
+    pub alert: String,
+}
+
+fn parse_colour(hex: &str) -> Color {
+    Srgba::hex(hex).map(Color::from).unwrap_or_else(|err| {
+        log::error!("Invalid theme colour {hex} ({err})");
+        Color::WHITE
+    })
+}
+
+impl ThemeColours {
+    pub fn background(&self) -> Color {
+        parse_colour(&self.background)
+    }
+
+    pub fn text(&self) -> Color {
+        parse_colour(&self.text)
+    }
+
+    pub fn highlight(&self) -> Color {
+        parse_colour(&self.highlight)
+    }
+
+    pub fn alert(&self) -> Color {
+        parse_colour(&self.alert)
+    }
+}
+
+/// The look of the game: the spritesheet and how it's cut up, colours and font.
+/// Themes live in [THEMES_DIR] and are picked with `--theme <name>` at startup.
+#[derive(Resource, Debug, Clone, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    /// Asset path of the spritesheet.
+    pub spritesheet: String,
+    /// Asset path of the font.
+    pub font: String,
+    /// Path of the animation clips (and character atlas grid) for the spritesheet.
+    pub animations: String,
+    /// The maze sprites, counted row by row like the indices below. The characters, fruit and
+    /// icons are cut up by the grid in [Theme::animations].
+    pub maze_grid: AtlasGrid,
+    pub dot: usize,
+    pub energizer: usize,
+    pub ghost_door: usize,
+    /// The maze sprite for every kind of wall piece.
+    walls: HashMap<WallType, usize>,
+    pub colours: ThemeColours,
+}
+
+impl Theme {
+    /// Loads the theme [name] from [THEMES_DIR], falling back to the default theme.
+    pub fn load_or_default(name: &str) -> Self {
+        load_ron_or(&format!("{THEMES_DIR}/{name}.ron"), "theme", Self::default)
+    }
+
+    /// Loads the theme given with `--theme <name>` on the command line, or the default theme.
+    pub fn from_args() -> Self {
+        let mut args = env::args().skip_while(|arg| arg != "--theme").skip(1);
+        let name = args.next().unwrap_or_else(|| DEFAULT_THEME.to_string());
+
+        log::info!("Using theme {name}");
+        Self::load_or_default(&name)
+    }
+
+    pub fn wall_sprite_index(&self, wall_type: &WallType) -> usize {
+        self.walls.get(wall_type).copied().unwrap_or_else(|| {
+            log::warn!("Theme {} has no sprite for wall {wall_type:?}", self.name);
+            0
+        })
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        ron::from_str(DEFAULT_THEME_RON).expect("The default theme should be valid")
+    }
+}
@@ -1,30 +1,40 @@
-use crate::{ghosts::ghost_mode::GhostModeRes, score::Score};
+use crate::{
+    debug::DebugRes,
+    ghosts::ghost_mode::GhostModeRes,
+    input::{Action, InputBindings},
+    pause::PauseRes,
+    rng::GameRng,
+    rules::GameRules,
+    theme::Theme,
+};
 use bevy::prelude::*;
 
 #[derive(Component)]
-pub struct ScoreText;
+pub struct DebugText;
 
 #[derive(Component)]
-pub struct DebugText;
+pub struct PausedText;
 
-pub fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+pub fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>, theme: Res<Theme>) {
     let text_font = TextFont {
-        font: asset_server.load("fonts/Joystix.ttf"),
+        font: asset_server.load(&theme.font),
         font_size: 67.0,
         ..default()
     };
 
-    // Score Text
+    // Paused Text
     commands.spawn((
-        Text::new("00"),
-        ScoreText,
+        Text::new("PAUSED"),
+        PausedText,
         text_font.clone(),
+        TextColor(theme.colours.text()),
         TextShadow::default(),
-        TextLayout::new_with_justify(JustifyText::Right),
+        Visibility::Hidden,
+        TextLayout::new_with_justify(JustifyText::Center),
         Node {
             position_type: PositionType::Absolute,
             top: Val::Px(100.),
-            left: Val::Px(80.),
+            width: Val::Percent(100.),
             ..default()
         },
     ));
@@ -47,16 +57,52 @@ pub fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
     ));
 }
 
-pub fn update_score_text(score: Res<Score>, mut score_text: Single<&mut Text, With<ScoreText>>) {
-    score_text.0 = format!("{:02}", score.score);
+pub fn update_paused_text(
+    pause: Res<PauseRes>,
+    mut paused_text: Single<&mut Visibility, With<PausedText>>,
+) {
+    **paused_text = if pause.paused {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
 }
 
 pub fn update_debug_text(
     ghost_mode: Res<GhostModeRes>,
+    rules: Res<GameRules>,
+    rng: Res<GameRng>,
+    debug: Res<DebugRes>,
+    time: Res<Time<Virtual>>,
+    bindings: Res<InputBindings>,
     mut debug_text: Single<&mut Text, With<DebugText>>,
 ) {
+    let simulation = if time.is_paused() {
+        "frozen".to_string()
+    } else {
+        format!("{}x", debug.simulation_speed())
+    };
+
+    let key = |action| bindings.key_name(action);
+
     debug_text.0 = format!(
-        "DEBUG :: {:?} (H to toggle)\nDisable debug mode (T)",
-        ghost_mode.global_mode
+        "DEBUG :: {:?} ({} to toggle)\nProfile: {}\nTargeting: {}\nCornering: {} px\nInput buffer: {}\nCo-op targeting: {}\nVersus ghost: {}\nSeed: {} ({})\nSimulation: {} ({} to freeze, {} to step, {} {} for speed)\nConsole ({})\nDisable debug mode ({})",
+        ghost_mode.global_mode,
+        key(Action::CycleGhostMode),
+        rules.profile,
+        rules.targeting,
+        rules.cornering,
+        rules.input_buffer,
+        rules.coop_targeting,
+        rules.versus_ghost,
+        rng.seed(),
+        rng.mode(),
+        simulation,
+        key(Action::FreezeSimulation),
+        key(Action::StepFrame),
+        key(Action::SlowDown),
+        key(Action::SpeedUp),
+        key(Action::OpenConsole),
+        key(Action::ToggleDebug)
     );
 }